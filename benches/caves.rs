@@ -0,0 +1,30 @@
+//! Compare counting the day 12 paths one by one with the memoized count.
+//! Run with `cargo bench --bench caves`.
+use aoc2021::{
+    aoc12,
+    bench::{measure, Config},
+};
+
+fn main() {
+    let map = match aoc12::load(aoc12::DATA) {
+        Ok(map) => map,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    let config = Config::default();
+
+    println!("{:<12} {:>12} {:>12}", "", "part1", "part2");
+    for (name, count) in [
+        (
+            "exhaustive",
+            aoc12::count_paths_exhaustive as fn(&_, bool) -> usize,
+        ),
+        ("memoized", aoc12::count_paths),
+    ] {
+        let [part1, part2] =
+            [false, true].map(|twice| measure(&config, || (), |_| count(&map, twice)));
+        println!("{:<12} {:>12.3?} {:>12.3?}", name, part1, part2);
+    }
+}
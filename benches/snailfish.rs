@@ -0,0 +1,42 @@
+//! Compare the tree and the flat representation of snailfish numbers on the day 18 input.
+//! Run with `cargo bench --bench snailfish`.
+use aoc2021::{
+    aoc18::{self, FlatNumber, Number, Snailfish},
+    bench::{measure, Config},
+};
+use std::time::Duration;
+
+fn time<N: Snailfish>(config: &Config, numbers: &[N]) -> [Duration; 2] {
+    [
+        measure(
+            config,
+            || numbers.to_vec(),
+            |numbers| aoc18::answer1(numbers.into_iter()),
+        ),
+        measure(
+            config,
+            || numbers.to_vec(),
+            |numbers| aoc18::answer2(numbers.into_iter()),
+        ),
+    ]
+}
+
+fn main() {
+    let numbers: Vec<Number> = match aoc18::load(aoc18::DATA) {
+        Ok(numbers) => numbers.collect(),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    let flat: Vec<FlatNumber> = numbers.iter().map(FlatNumber::from).collect();
+    let config = Config::default();
+
+    println!("{:<6} {:>12} {:>12}", "", "part1", "part2");
+    for (name, [part1, part2]) in [
+        ("tree", time(&config, &numbers)),
+        ("flat", time(&config, &flat)),
+    ] {
+        println!("{:<6} {:>12.3?} {:>12.3?}", name, part1, part2);
+    }
+}
@@ -0,0 +1,22 @@
+//! Decode arbitrary transmissions, which must either fail with an error or decode to a packet
+//! that encodes back to the same packet. Run with `cargo fuzz run bits_decode`.
+#![no_main]
+
+use aoc2021::aoc16::{self, Packet};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    let listing = aoc16::disassemble(input);
+    match aoc16::parse(input) {
+        Ok(packet) => {
+            assert!(listing.is_ok());
+            let _ = packet.to_string();
+            let encoded: Packet = aoc16::parse(&packet.to_hex()).unwrap();
+            assert_eq!(encoded, packet);
+        }
+        Err(_) => assert!(listing.is_err()),
+    }
+});
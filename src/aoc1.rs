@@ -1,4 +1,5 @@
-pub use crate::loaders::file_to_vec as load;
+pub use crate::loaders::{file_to_vec as load, str_to_vec as parse};
+use crate::{Day, Result};
 
 pub const DATA: &str = "input/aoc1";
 
@@ -10,6 +11,29 @@ pub fn answer2(input: &[u32]) -> usize {
     answer1(&input.windows(3).map(|x| x.iter().sum()).collect::<Vec<_>>())
 }
 
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 1;
+    const DATA: &'static str = DATA;
+
+    type Input = Vec<u32>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(&input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(&input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -25,14 +49,4 @@ mod tests {
     fn test_answer2_mock_data() {
         assert_eq!(answer2(MOCK_DATA), 5)
     }
-
-    #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(&load(DATA)), 1624)
-    }
-
-    #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(&load(DATA)), 1653)
-    }
 }
@@ -1,14 +1,33 @@
-pub use crate::loaders::file_to_lines as load;
+use crate::{loaders::parse_file, Day, Error, Result};
 use itertools::Itertools;
 pub const DATA: &str = "input/aoc10";
 
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<impl Iterator<Item = String>> {
+    parse_file(filename, parse)
+}
+
+/// Read the lines of the navigation subsystem, which may only hold brackets
+pub fn parse(input: &str) -> Result<impl Iterator<Item = String>> {
+    let lines: Vec<String> = input
+        .lines()
+        .map(|line| {
+            if let Some((i, c)) = line.char_indices().find(|&(_, c)| !"([{<)]}>".contains(c)) {
+                let bracket = &line[i..i + c.len_utf8()];
+                return Err(Error::at(input, bracket, "Expected one of `([{<)]}>`"));
+            }
+            Ok(line.to_owned())
+        })
+        .try_collect()?;
+    Ok(lines.into_iter())
+}
+
+/// Points of a corrupted closer, parsing leaves nothing but brackets
 fn byte_to_points(byte: &u8) -> u32 {
     match byte {
         b')' => 3,
         b']' => 57,
         b'}' => 1197,
-        b'>' => 25137,
-        bad => panic!("Unexpected char {}", bad),
+        _ => 25137,
     }
 }
 
@@ -22,11 +41,9 @@ pub fn answer1(input: impl Iterator<Item = String>) -> u32 {
                     _ => {
                         if stack
                             .pop()
-                            .map(|opener| byte.wrapping_sub(*opener) <= 2)
-                            .unwrap()
+                            .is_some_and(|opener| byte.wrapping_sub(*opener) <= 2)
                         {
                             continue; // Line is good so far, check next byte
-                        } else {
                         }
                         return byte_to_points(byte); // Incorrect closer, yield points
                     }
@@ -37,8 +54,8 @@ pub fn answer1(input: impl Iterator<Item = String>) -> u32 {
         .sum()
 }
 
-pub fn answer2(input: impl Iterator<Item = String>) -> u64 {
-    let mut scores = input
+pub fn answer2(input: impl Iterator<Item = String>) -> Result<u64> {
+    let mut scores: Vec<u64> = input
         .filter_map(|line| {
             let mut stack = Vec::with_capacity(line.len());
             for byte in line.as_bytes() {
@@ -47,11 +64,9 @@ pub fn answer2(input: impl Iterator<Item = String>) -> u64 {
                     _ => {
                         if stack
                             .pop()
-                            .map(|opener| byte.wrapping_sub(*opener) <= 2)
-                            .unwrap()
+                            .is_some_and(|opener| byte.wrapping_sub(*opener) <= 2)
                         {
                             continue; // Line is good so far, check next byte
-                        } else {
                         }
                         return None; // Incorrect closer, ignore
                     }
@@ -68,21 +83,48 @@ pub fn answer2(input: impl Iterator<Item = String>) -> u64 {
                         b'(' => 1,
                         b'[' => 2,
                         b'{' => 3,
-                        b'<' => 4,
-                        _ => panic!(),
+                        _ => 4, // Only openers are pushed
                     })
-                    .fold(0, |total, point| 5 * total + point),
+                    .try_fold(0u64, |total, point| {
+                        total.checked_mul(5)?.checked_add(point)
+                    }),
             )
         })
-        .collect_vec();
+        .collect::<Option<_>>()
+        .ok_or_else(|| Error::solve("Completion score doesn't fit in a u64"))?;
+    if scores.is_empty() {
+        return Err(Error::solve("Expected at least one incomplete line"));
+    }
     scores.sort();
-    scores[scores.len() / 2]
+    Ok(scores[scores.len() / 2])
+}
+
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 10;
+    const DATA: &'static str = DATA;
+
+    type Input = Vec<String>;
+    type Answer1 = u32;
+    type Answer2 = Result<u64>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(parse(input)?.collect())
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(input.into_iter())
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(input.into_iter())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mockers::str_lines_to_string;
 
     const MOCK_DATA: &str = concat!(
         "[({(<(())[]>[[{[]{<()<>>\n",
@@ -99,21 +141,37 @@ mod tests {
 
     #[test]
     fn test_answer1_mock_data() {
-        assert_eq!(answer1(str_lines_to_string(MOCK_DATA)), 26397)
+        assert_eq!(answer1(parse(MOCK_DATA).unwrap()), 26397)
     }
 
     #[test]
     fn test_answer2_mock_data() {
-        assert_eq!(answer2(str_lines_to_string(MOCK_DATA)), 288957)
+        assert_eq!(answer2(parse(MOCK_DATA).unwrap()).unwrap(), 288957)
     }
 
     #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(load(DATA)), 193275)
+    fn test_no_incomplete_lines() {
+        assert!(matches!(
+            answer2(parse("").unwrap()),
+            Err(Error::Solve { .. })
+        ));
+        assert!(matches!(
+            answer2(parse(&"(".repeat(100)).unwrap()),
+            Err(Error::Solve { .. })
+        ));
     }
 
     #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(load(DATA)), 2429644557)
+    fn test_parse_error() {
+        assert!(matches!(
+            parse("[()]\n[(x)]\n").err(),
+            Some(Error::Parse {
+                line: 2,
+                column: 3,
+                ..
+            })
+        ));
+        // A closer without an opener is corrupted, rather than a parse error
+        assert_eq!(answer1(parse("()]\n").unwrap()), 57);
     }
 }
@@ -1,30 +1,35 @@
-pub use crate::loaders::file_to_squashed_2d_vec as load;
+use crate::{grid::Grid, loaders::parse_file, Day, Result};
 use std::iter::repeat_with;
 
-use itertools::{iproduct, Itertools};
+use itertools::Itertools;
 
 pub const DATA: &str = "input/aoc11";
 
-type Octopuses = Vec<u32>; // Vector of each octopus count
+type Octopuses = Grid<u32>; // Grid of each octopus count
 type Neighbours = Vec<Vec<usize>>; // Vector of vectors, listing each octopus neighbor
 
-/// Lookup table for neighbours, saves time at the cost of memory
-fn bake_neighbour_lookup(n_rows: usize, n_cols: usize) -> Neighbours {
-    let row_ranges = (0..n_rows).map(|row| row.saturating_sub(1)..n_rows.min(row + 2));
-    let column_ranges = (0..n_cols).map(|col| col.saturating_sub(1)..n_cols.min(col + 2));
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<Octopuses> {
+    parse_file(filename, parse)
+}
 
-    iproduct!(row_ranges, column_ranges)
-        .enumerate()
-        .map(|(idx, (rows, columns))| {
-            iproduct![rows, columns]
-                .map(|(row, col)| n_cols * row + col)
-                .filter(|&i| i != idx) // current cell is not a neighbour
+pub fn parse(input: &str) -> Result<Octopuses> {
+    Grid::parse_digits(input)
+}
+
+/// Lookup table for neighbours by flat index, saves time at the cost of memory
+fn bake_neighbour_lookup(octopuses: &Octopuses) -> Neighbours {
+    octopuses
+        .points()
+        .map(|point| {
+            octopuses
+                .neighbours8(point)
+                .map(|nbor| octopuses.index_of(nbor))
                 .collect()
         })
         .collect()
 }
 
-fn flash(octopuses: &mut Octopuses, neighbours: &Neighbours) -> u32 {
+fn flash(octopuses: &mut [u32], neighbours: &Neighbours) -> u32 {
     // First increment + detect which octopuses are ready to flash
     let mut will_flash = octopuses
         .iter_mut()
@@ -58,23 +63,46 @@ fn flash(octopuses: &mut Octopuses, neighbours: &Neighbours) -> u32 {
     total_flashed
 }
 
-pub fn answer1((mut octopuses, width): (Octopuses, usize)) -> u32 {
-    let neighbours = bake_neighbour_lookup(octopuses.len() / width, width);
-    repeat_with(|| flash(&mut octopuses, &neighbours))
+pub fn answer1(mut octopuses: Octopuses) -> u32 {
+    let neighbours = bake_neighbour_lookup(&octopuses);
+    repeat_with(|| flash(octopuses.as_mut_slice(), &neighbours))
         .take(100)
         .sum()
 }
 
-pub fn answer2((mut octopuses, width): (Octopuses, usize)) -> u32 {
-    let neighbours = bake_neighbour_lookup(octopuses.len() / width, width);
+pub fn answer2(mut octopuses: Octopuses) -> u32 {
+    let neighbours = bake_neighbour_lookup(&octopuses);
     let mut iterations = 0;
     while !octopuses.iter().all_equal() {
         iterations += 1;
-        flash(&mut octopuses, &neighbours);
+        flash(octopuses.as_mut_slice(), &neighbours);
     }
     iterations
 }
 
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 11;
+    const DATA: &'static str = DATA;
+
+    type Input = Octopuses;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -96,21 +124,14 @@ mod tests {
 
     #[test]
     fn test_answer1_mock_data() {
-        assert_eq!(answer1((MOCK_DATA.to_vec(), MOCK_WIDTH)), 1656)
+        assert_eq!(
+            answer1(Grid::from_vec(MOCK_DATA.to_vec(), MOCK_WIDTH)),
+            1656
+        )
     }
 
     #[test]
     fn test_answer2_mock_data() {
-        assert_eq!(answer2((MOCK_DATA.to_vec(), MOCK_WIDTH)), 195)
-    }
-
-    #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(load(DATA)), 1601)
-    }
-
-    #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(load(DATA)), 368)
+        assert_eq!(answer2(Grid::from_vec(MOCK_DATA.to_vec(), MOCK_WIDTH)), 195)
     }
 }
@@ -1,46 +1,175 @@
-use crate::loaders::file_to_lines;
-use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
+use crate::{loaders::parse_file, Day, Error, Result};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    hash::Hash,
+};
 pub const DATA: &str = "input/aoc12";
 
+/// A cave, as its index among the interned names of a `CaveMap`
 pub type Cave = u32;
 const START: Cave = 0;
 const END: Cave = 1;
-type CaveMap = HashMap<Cave, Vec<Cave>>;
 
-/// Encodes a &str representation of a cave to a 32 bit number according to:
-/// "start" => START (0)
-/// "end" => END (1)
-/// u32 of u8 representations packed next to eachother. e.g. `"a"` would be 97, and `"aa"` 97*(1 + 2**8) = 24929
-fn str_to_cave(s: &str) -> Cave {
-    match s {
-        "start" => START,
-        "end" => END,
-        _ => s.bytes().fold(0, |val, bit| val << 8 | bit as Cave), // This might as well be << 7 since ASCII fits in 7 bits
+/// The caves and the connections between them. Caves are interned in the order they are first
+/// mentioned, after `start` and `end`. No connections lead back to `start` or away from `end`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaveMap {
+    names: Vec<String>,
+    small: Vec<bool>,
+    connections: Vec<Vec<Cave>>,
+}
+
+impl CaveMap {
+    /// The number of caves
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Every cave, starting with `start` and `end`
+    pub fn caves(&self) -> impl Iterator<Item = Cave> {
+        0..self.names.len() as Cave
+    }
+
+    pub fn name(&self, cave: Cave) -> &str {
+        &self.names[cave as usize]
+    }
+
+    /// The cave with a name, if there is one
+    pub fn cave(&self, name: &str) -> Option<Cave> {
+        self.names
+            .iter()
+            .position(|other| other == name)
+            .map(|i| i as Cave)
+    }
+
+    /// Whether a cave is small, `start` and `end` are neither small nor large but count as large
+    /// since they can't be revisited anyway
+    pub fn is_small(&self, cave: Cave) -> bool {
+        self.small[cave as usize]
+    }
+
+    /// The caves which can be entered from a cave
+    pub fn connections(&self, cave: Cave) -> &[Cave] {
+        &self.connections[cave as usize]
+    }
+
+    /// The map as an undirected Graphviz graph. Small caves are circles, large caves grey boxes,
+    /// and `start` and `end` green and red. The caves and passages of `path`, given by name, are
+    /// drawn in bold red.
+    pub fn to_dot(&self, path: Option<&[&str]>) -> String {
+        let path = path.unwrap_or_default();
+        let on_path: HashSet<&str> = path.iter().copied().collect();
+        let steps: HashSet<(&str, &str)> = path
+            .windows(2)
+            .flat_map(|step| [(step[0], step[1]), (step[1], step[0])])
+            .collect();
+        let highlight = |on_path| {
+            if on_path {
+                ", color=red, penwidth=2"
+            } else {
+                ""
+            }
+        };
+
+        let mut dot = String::from("graph caves {\n");
+        for cave in self.caves() {
+            let name = self.name(cave);
+            let style = match cave {
+                START => "shape=doublecircle, style=filled, fillcolor=palegreen",
+                END => "shape=doublecircle, style=filled, fillcolor=lightcoral",
+                _ if self.is_small(cave) => "shape=circle",
+                _ => "shape=box, style=filled, fillcolor=lightgrey",
+            };
+            let highlight = highlight(on_path.contains(name));
+            dot += &format!("    \"{}\" [{}{}];\n", name, style, highlight);
+        }
+        // Passages are stored one way or both ways, list each once
+        let passages: BTreeSet<(Cave, Cave)> = self
+            .caves()
+            .flat_map(|from| {
+                self.connections(from)
+                    .iter()
+                    .map(move |&to| (from.min(to), from.max(to)))
+            })
+            .collect();
+        for (a, b) in passages {
+            let (a, b) = (self.name(a), self.name(b));
+            let highlight = highlight(steps.contains(&(a, b)));
+            let attributes = if highlight.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", highlight.trim_start_matches(", "))
+            };
+            dot += &format!("    \"{}\" -- \"{}\"{};\n", a, b, attributes);
+        }
+        dot += "}\n";
+        dot
     }
 }
 
-fn is_small_cave(&cave: &Cave) -> bool {
-    /*
-    We assume all caves are either start, end, or 2 letters. This allows to check the case with a
-    simple comparison, making start and end considered large caves (the non-special case)
-    */
-    const SMALL_CAVE_AA: Cave = 24929; // str_to_cave("aa")
-    cave >= SMALL_CAVE_AA
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<CaveMap> {
+    parse_file(filename, parse)
 }
 
-pub fn load(filename: &str) -> CaveMap {
-    lines_to_cave_map(file_to_lines(filename))
+/// The cave with a name, adding it to the map if it's new
+fn intern<'a>(
+    input: &str,
+    name: &'a str,
+    map: &mut CaveMap,
+    ids: &mut HashMap<&'a str, Cave>,
+) -> Result<Cave> {
+    if let Some(&cave) = ids.get(name) {
+        return Ok(cave);
+    }
+    let small = if !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase()) {
+        true
+    } else if !name.is_empty() && name.chars().all(|c| c.is_ascii_uppercase()) {
+        false
+    } else {
+        let reason = "Expected a cave name of all lowercase or all uppercase letters";
+        return Err(Error::at(input, name, reason));
+    };
+    let cave = map.names.len() as Cave;
+    ids.insert(name, cave);
+    map.names.push(name.to_owned());
+    map.small.push(small);
+    map.connections.push(Vec::new());
+    Ok(cave)
 }
 
-fn lines_to_cave_map(lines: impl Iterator<Item = String>) -> CaveMap {
-    lines
-        .flat_map(|line| {
-            let (from, to) = line.split('-').map(str_to_cave).next_tuple().unwrap();
-            [(from, to), (to, from)]
-        })
-        .filter(|(from, to)| *from != END && *to != START)
-        .into_group_map()
+pub fn parse(input: &str) -> Result<CaveMap> {
+    let mut map = CaveMap {
+        names: vec!["start".to_owned(), "end".to_owned()],
+        small: vec![false, false],
+        connections: vec![Vec::new(), Vec::new()],
+    };
+    let mut ids: HashMap<&str, Cave> = HashMap::from([("start", START), ("end", END)]);
+    let mut edges = Vec::new();
+    for line in input.lines().filter(|line| !line.trim().is_empty()) {
+        let (from, to) = line
+            .trim()
+            .split_once('-')
+            .ok_or_else(|| Error::at(input, line, "Expected a connection like `a-b`"))?;
+        let from = intern(input, from, &mut map, &mut ids)?;
+        let to = intern(input, to, &mut map, &mut ids)?;
+        let large = |cave| cave > END && !map.is_small(cave);
+        if large(from) && large(to) {
+            // Paths could go back and forth between the caves forever
+            let reason = "Expected no passage between two large caves";
+            return Err(Error::at(input, line, reason));
+        }
+        edges.extend([(from, to), (to, from)]);
+    }
+    for (from, to) in edges {
+        if from != END && to != START {
+            map.connections[from as usize].push(to);
+        }
+    }
+    Ok(map)
 }
 
 fn traverse(
@@ -50,10 +179,10 @@ fn traverse(
     may_visit_small_cave_twice: bool,
 ) -> usize {
     path.insert(current_cave);
-    map[&current_cave]
+    map.connections(current_cave)
         .iter()
         .filter_map(|cave_candidate| {
-            if is_small_cave(cave_candidate) {
+            if map.is_small(*cave_candidate) {
                 if !path.contains(cave_candidate) {
                     Some((cave_candidate, may_visit_small_cave_twice)) // New small cave
                 } else if may_visit_small_cave_twice {
@@ -75,19 +204,377 @@ fn traverse(
         .sum()
 }
 
+/// Rules for which caves a path may enter, tracked through a state which is updated on every
+/// cave entered. The state is used for memoization when counting, so it should hold no more than
+/// what the rules need.
+pub trait VisitPolicy {
+    type State: Clone + Eq + Hash;
+
+    /// The state of a path which has only been in `start`
+    fn start(&self) -> Self::State;
+
+    /// The state after entering `cave`, or `None` if the cave may not be entered
+    fn enter(&self, map: &CaveMap, state: &Self::State, cave: Cave) -> Option<Self::State>;
+
+    /// Whether a path which reached `end` with this state counts
+    fn accept(&self, _state: &Self::State) -> bool {
+        true
+    }
+}
+
+/// Most visits to a single small cave a `SmallCaves` policy may allow. Large caves only lead to
+/// small caves or `end`, so this keeps paths, and the recursion counting them, finite.
+pub const MAX_VISITS: usize = 16;
+
+/// Limits on visiting small caves, large caves may always be visited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmallCaves {
+    /// Times any single small cave may be visited
+    max_visits: usize,
+    /// Visits to small caves which have already been visited, in total over the path
+    max_revisits: usize,
+}
+
+/// A policy allowing more visits to each small cave than `MAX_VISITS`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TooManyVisits(pub usize);
+
+impl std::fmt::Display for TooManyVisits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Expected at most {} visits to each small cave, found {}",
+            MAX_VISITS, self.0
+        )
+    }
+}
+
+impl std::error::Error for TooManyVisits {}
+
+impl SmallCaves {
+    pub fn new(max_visits: usize, max_revisits: usize) -> Result<Self, TooManyVisits> {
+        if max_visits > MAX_VISITS {
+            return Err(TooManyVisits(max_visits));
+        }
+        Ok(SmallCaves {
+            max_visits,
+            max_revisits,
+        })
+    }
+
+    pub fn max_visits(&self) -> usize {
+        self.max_visits
+    }
+
+    pub fn max_revisits(&self) -> usize {
+        self.max_revisits
+    }
+
+    /// Every small cave at most once, as in part 1
+    pub const ONCE: Self = SmallCaves {
+        max_visits: 1,
+        max_revisits: 0,
+    };
+    /// A single small cave twice and the others at most once, as in part 2
+    pub const SINGLE_TWICE: Self = SmallCaves {
+        max_visits: 2,
+        max_revisits: 1,
+    };
+}
+
+impl VisitPolicy for SmallCaves {
+    /// Visits to each small cave, and the total number of revisits
+    type State = (BTreeMap<Cave, usize>, usize);
+
+    fn start(&self) -> Self::State {
+        (BTreeMap::new(), 0)
+    }
+
+    fn enter(
+        &self,
+        map: &CaveMap,
+        (visits, revisits): &Self::State,
+        cave: Cave,
+    ) -> Option<Self::State> {
+        if !map.is_small(cave) {
+            return Some((visits.clone(), *revisits));
+        }
+        let previous = visits.get(&cave).copied().unwrap_or_default();
+        let revisits = revisits + (previous > 0) as usize;
+        if previous >= self.max_visits || revisits > self.max_revisits {
+            return None;
+        }
+        let mut visits = visits.clone();
+        visits.insert(cave, previous + 1);
+        Some((visits, revisits))
+    }
+}
+
+/// Never enter some caves, given by name, on top of the rules of another policy
+#[derive(Debug, Clone)]
+pub struct Forbid<P> {
+    pub caves: HashSet<String>,
+    pub policy: P,
+}
+
+impl<P> Forbid<P> {
+    pub fn new<'a>(caves: impl IntoIterator<Item = &'a str>, policy: P) -> Self {
+        Self {
+            caves: caves.into_iter().map(str::to_owned).collect(),
+            policy,
+        }
+    }
+}
+
+impl<P: VisitPolicy> VisitPolicy for Forbid<P> {
+    type State = P::State;
+
+    fn start(&self) -> Self::State {
+        self.policy.start()
+    }
+
+    fn enter(&self, map: &CaveMap, state: &Self::State, cave: Cave) -> Option<Self::State> {
+        if self.caves.contains(map.name(cave)) {
+            return None;
+        }
+        self.policy.enter(map, state, cave)
+    }
+
+    fn accept(&self, state: &Self::State) -> bool {
+        self.policy.accept(state)
+    }
+}
+
+/// Only accept paths through all of some caves, given by name, on top of the rules of another
+/// policy
+#[derive(Debug, Clone)]
+pub struct Require<P> {
+    pub caves: HashSet<String>,
+    pub policy: P,
+}
+
+impl<P> Require<P> {
+    pub fn new<'a>(caves: impl IntoIterator<Item = &'a str>, policy: P) -> Self {
+        Self {
+            caves: caves.into_iter().map(str::to_owned).collect(),
+            policy,
+        }
+    }
+}
+
+impl<P: VisitPolicy> VisitPolicy for Require<P> {
+    /// The state of the other policy, and the required caves visited so far
+    type State = (P::State, BTreeSet<Cave>);
+
+    fn start(&self) -> Self::State {
+        (self.policy.start(), BTreeSet::new())
+    }
+
+    fn enter(
+        &self,
+        map: &CaveMap,
+        (state, visited): &Self::State,
+        cave: Cave,
+    ) -> Option<Self::State> {
+        let state = self.policy.enter(map, state, cave)?;
+        let mut visited = visited.clone();
+        if self.caves.contains(map.name(cave)) {
+            visited.insert(cave);
+        }
+        Some((state, visited))
+    }
+
+    fn accept(&self, (state, visited): &Self::State) -> bool {
+        visited.len() == self.caves.len() && self.policy.accept(state)
+    }
+}
+
+/// Depth first iterator over every path from `start` to `end`, as the names of the caves
+pub struct Paths<'a, P: VisitPolicy> {
+    map: &'a CaveMap,
+    policy: P,
+    /// The caves of the current path, each with the index of the next connection to try from it
+    /// and the state of the policy after entering it
+    stack: Vec<(Cave, usize, P::State)>,
+}
+
+impl<'a, P: VisitPolicy> Iterator for Paths<'a, P> {
+    type Item = Vec<&'a str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((cave, next, state)) = self.stack.last_mut() {
+            let Some(&candidate) = self.map.connections(*cave).get(*next) else {
+                self.stack.pop(); // Every connection has been tried, backtrack
+                continue;
+            };
+            *next += 1;
+            let Some(state) = self.policy.enter(self.map, state, candidate) else {
+                continue;
+            };
+            if candidate == END {
+                if !self.policy.accept(&state) {
+                    continue;
+                }
+                let path = self.stack.iter().map(|&(cave, ..)| cave).chain([END]);
+                return Some(path.map(|cave| self.map.name(cave)).collect());
+            }
+            self.stack.push((candidate, 0, state));
+        }
+        None
+    }
+}
+
+/// Every path through the caves which follows the policy
+pub fn paths<P: VisitPolicy>(map: &CaveMap, policy: P) -> Paths<'_, P> {
+    let start = policy.start();
+    Paths {
+        map,
+        policy,
+        stack: vec![(START, 0, start)],
+    }
+}
+
+/// Count the paths which follow the policy, each state of the policy in each cave is only
+/// counted once
+pub fn count_paths_with<P: VisitPolicy>(map: &CaveMap, policy: &P) -> usize {
+    fn count_from<P: VisitPolicy>(
+        cave: Cave,
+        state: P::State,
+        map: &CaveMap,
+        policy: &P,
+        memo: &mut HashMap<(Cave, P::State), usize>,
+    ) -> usize {
+        if cave == END {
+            return policy.accept(&state) as usize;
+        }
+        if let Some(&count) = memo.get(&(cave, state.clone())) {
+            return count;
+        }
+        let count = map
+            .connections(cave)
+            .iter()
+            .filter_map(|&next| Some((next, policy.enter(map, &state, next)?)))
+            .map(|(next, state)| count_from(next, state, map, policy, memo))
+            .sum();
+        memo.insert((cave, state), count);
+        count
+    }
+    count_from(START, policy.start(), map, policy, &mut HashMap::new())
+}
+
+/// Count the paths by exploring each of them, see `count_paths` for a faster way
+pub fn count_paths_exhaustive(map: &CaveMap, may_visit_small_cave_twice: bool) -> usize {
+    traverse(
+        START,
+        HashSet::with_capacity(map.len()),
+        map,
+        may_visit_small_cave_twice,
+    )
+}
+
+/// The small caves visited so far as a bitset, with a bit for each small cave
+type Visited = Vec<u64>;
+
+/// The state of a traversal: the current cave, the small caves visited so far, and whether a
+/// small cave may still be visited twice
+type State = (Cave, Visited, bool);
+
+/// Count the paths from `cave` to the end, each state is only counted once
+fn count_from(
+    (cave, mut visited, may_visit_small_cave_twice): State,
+    map: &CaveMap,
+    small_caves: &HashMap<Cave, usize>,
+    memo: &mut HashMap<State, usize>,
+) -> usize {
+    if cave == END {
+        return 1;
+    }
+    if let Some(&i) = small_caves.get(&cave) {
+        visited[i / 64] |= 1 << (i % 64);
+    }
+    let state = (cave, visited, may_visit_small_cave_twice);
+    if let Some(&count) = memo.get(&state) {
+        return count;
+    }
+    let (_, visited, _) = &state;
+    let is_visited = |cave| {
+        small_caves
+            .get(cave)
+            .is_some_and(|&i| visited[i / 64] >> (i % 64) & 1 == 1)
+    };
+    let count = map
+        .connections(cave)
+        .iter()
+        .filter_map(|next| {
+            if !is_visited(next) {
+                Some((*next, visited.clone(), may_visit_small_cave_twice))
+            } else if may_visit_small_cave_twice {
+                Some((*next, visited.clone(), false)) // First re-visited small cave
+            } else {
+                None
+            }
+        })
+        .map(|state| count_from(state, map, small_caves, memo))
+        .sum();
+    memo.insert(state, count);
+    count
+}
+
+/// Count the paths with memoization, keeping the visited small caves as a bitset
+pub fn count_paths(map: &CaveMap, may_visit_small_cave_twice: bool) -> usize {
+    let small_caves: HashMap<Cave, usize> = map
+        .caves()
+        .filter(|&cave| map.is_small(cave))
+        .zip(0..)
+        .collect();
+    count_from(
+        (
+            START,
+            vec![0; small_caves.len().div_ceil(64)],
+            may_visit_small_cave_twice,
+        ),
+        map,
+        &small_caves,
+        &mut HashMap::new(),
+    )
+}
+
 pub fn answer1(input: CaveMap) -> usize {
-    traverse(START, HashSet::with_capacity(input.len()), &input, false)
+    count_paths(&input, false)
 }
 
 pub fn answer2(input: CaveMap) -> usize {
-    traverse(START, HashSet::with_capacity(input.len()), &input, true)
+    count_paths(&input, true)
+}
+
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 12;
+    const DATA: &'static str = DATA;
+
+    type Input = CaveMap;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mockers::str_lines_to_string;
-    use itertools::zip_eq;
+    use itertools::{zip_eq, Itertools};
+    use std::iter;
 
     const MOCK_DATA: [&str; 2] = [
         concat!(
@@ -127,30 +614,197 @@ mod tests {
     #[test]
     fn test_answer1_mock_data() {
         for (data, result) in zip_eq(MOCK_DATA, [19, 226]) {
-            assert_eq!(
-                answer1(lines_to_cave_map(str_lines_to_string(data))),
-                result
-            )
+            assert_eq!(answer1(parse(data).unwrap()), result)
         }
     }
 
     #[test]
     fn test_answer2_mock_data() {
         for (data, result) in zip_eq(MOCK_DATA, [103, 3509]) {
-            assert_eq!(
-                answer2(lines_to_cave_map(str_lines_to_string(data))),
-                result
-            )
+            assert_eq!(answer2(parse(data).unwrap()), result)
         }
     }
 
     #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(load(DATA)), 4720)
+    fn test_count_paths() {
+        for data in MOCK_DATA {
+            let map = parse(data).unwrap();
+            for twice in [false, true] {
+                assert_eq!(
+                    count_paths(&map, twice),
+                    count_paths_exhaustive(&map, twice)
+                );
+            }
+        }
+        // A big cave next to 12 small ones, far too many paths to explore one by one
+        let small_caves = (b'a'..=b'l').map(|c| format!("{}{}", c as char, c as char));
+        let data = ["start-AA".to_owned(), "AA-end".to_owned()]
+            .into_iter()
+            .chain(small_caves.map(|cave| format!("AA-{}", cave)))
+            .join("\n");
+        let map = parse(&data).unwrap();
+        // Every ordered selection of the small caves, with big cave visits between them
+        let selections: usize = (0..=12).map(|k| (12 - k + 1..=12).product::<usize>()).sum();
+        assert_eq!(count_paths(&map, false), selections);
+        // The same big cave at the end of a corridor of small caves, more than fit in a u64
+        let names = (0..60)
+            .map(|i| format!("{}{}", (b'm' + i / 26) as char, (b'a' + i % 26) as char))
+            .collect_vec();
+        let corridor = iter::once("start")
+            .chain(names.iter().map(String::as_str))
+            .chain(iter::once("AA"))
+            .tuple_windows()
+            .map(|(from, to)| format!("{}-{}", from, to))
+            .join("\n");
+        let data = data.replace("start-AA", &corridor);
+        let map = parse(&data).unwrap();
+        assert_eq!(map.caves().filter(|&cave| map.is_small(cave)).count(), 72);
+        assert_eq!(count_paths(&map, false), selections);
+    }
+
+    #[test]
+    fn test_cave_names() {
+        let map =
+            parse("start-A\nA-b\nA-cavern\nstart-LONGHALL\nb-end\ncavern-end\nLONGHALL-end\n")
+                .unwrap();
+        assert_eq!(map.len(), 6);
+        let cave = |name| map.cave(name).unwrap();
+        assert!(map.is_small(cave("b")) && map.is_small(cave("cavern")));
+        assert!(!map.is_small(cave("A")) && !map.is_small(cave("LONGHALL")));
+        assert!(!map.is_small(cave("start")) && !map.is_small(cave("end")));
+        assert_eq!(
+            paths(&map, SmallCaves::ONCE)
+                .map(|path| path.join(","))
+                .sorted()
+                .collect_vec(),
+            [
+                "start,A,b,A,cavern,end",
+                "start,A,b,end",
+                "start,A,cavern,A,b,end",
+                "start,A,cavern,end",
+                "start,LONGHALL,end",
+            ]
+        );
+        assert_eq!(answer1(map), 5);
+
+        let err = parse("start-A\nA-Cave\n").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Parse {
+                line: 2,
+                column: 3,
+                ..
+            }
+        ));
+        assert!(parse("start-\n").is_err());
+
+        let err = parse("start-A\nA-B\nB-end\n").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Parse {
+                line: 2,
+                column: 1,
+                ..
+            }
+        ));
+        assert!(parse("start-A\nA-A\nA-end\n").is_err());
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let map = parse("start-A\nA-b\nb-end\nA-end\n").unwrap();
+        assert_eq!(
+            map.to_dot(Some(&["start", "A", "b", "end"])),
+            concat!(
+                "graph caves {\n",
+                "    \"start\" [shape=doublecircle, style=filled, fillcolor=palegreen, color=red, penwidth=2];\n",
+                "    \"end\" [shape=doublecircle, style=filled, fillcolor=lightcoral, color=red, penwidth=2];\n",
+                "    \"A\" [shape=box, style=filled, fillcolor=lightgrey, color=red, penwidth=2];\n",
+                "    \"b\" [shape=circle, color=red, penwidth=2];\n",
+                "    \"start\" -- \"A\" [color=red, penwidth=2];\n",
+                "    \"end\" -- \"A\";\n",
+                "    \"end\" -- \"b\" [color=red, penwidth=2];\n",
+                "    \"A\" -- \"b\" [color=red, penwidth=2];\n",
+                "}\n",
+            )
+        );
+        assert!(!map.to_dot(None).contains("red"));
+    }
+
+    #[test]
+    fn test_policies() {
+        let map = parse(MOCK_DATA[0]).unwrap();
+        let count = |policy| count_paths_with(&map, &policy);
+        assert_eq!(count(SmallCaves::ONCE), 19);
+        assert_eq!(count(SmallCaves::SINGLE_TWICE), 103);
+        // Any small cave twice is a superset of only a single one twice
+        let any_twice = SmallCaves::new(2, usize::MAX).unwrap();
+        assert!(count(any_twice) > 103);
+        assert_eq!(
+            count(any_twice),
+            paths(&map, any_twice).count(),
+            "Counting and listing should agree"
+        );
+        assert_eq!(
+            SmallCaves::new(usize::MAX, usize::MAX),
+            Err(TooManyVisits(usize::MAX))
+        );
+        // Back and forth between A and b as many times as allowed
+        let most = SmallCaves::new(MAX_VISITS, usize::MAX).unwrap();
+        let star = parse("start-A\nA-b\nA-end\n").unwrap();
+        assert_eq!(count_paths_with(&star, &most), MAX_VISITS + 1);
+        assert_eq!(paths(&star, most).count(), MAX_VISITS + 1);
+        let without_kj = Forbid::new(["kj"], SmallCaves::ONCE);
+        assert_eq!(count_paths_with(&map, &without_kj), 5);
+        assert!(paths(&map, without_kj).all(|path| !path.contains(&"kj")));
+        let through_sa = Require::new(["sa"], SmallCaves::ONCE);
+        assert_eq!(
+            paths(&map, through_sa).count(),
+            0,
+            "sa is a dead end which can't be left without revisiting kj"
+        );
+        let through_sa = Require::new(["sa"], SmallCaves::SINGLE_TWICE);
+        assert!(count_paths_with(&map, &through_sa) > 0);
+        assert!(paths(&map, through_sa).all(|path| path.contains(&"sa")));
     }
 
     #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(load(DATA)), 147848)
+    fn test_paths() {
+        let map = parse(MOCK_DATA[0]).unwrap();
+        let listed = paths(&map, SmallCaves::ONCE)
+            .map(|path| path.join(","))
+            .sorted()
+            .collect_vec();
+        assert_eq!(
+            listed,
+            [
+                "start,HN,dc,HN,end",
+                "start,HN,dc,HN,kj,HN,end",
+                "start,HN,dc,end",
+                "start,HN,dc,kj,HN,end",
+                "start,HN,end",
+                "start,HN,kj,HN,dc,HN,end",
+                "start,HN,kj,HN,dc,end",
+                "start,HN,kj,HN,end",
+                "start,HN,kj,dc,HN,end",
+                "start,HN,kj,dc,end",
+                "start,dc,HN,end",
+                "start,dc,HN,kj,HN,end",
+                "start,dc,end",
+                "start,dc,kj,HN,end",
+                "start,kj,HN,dc,HN,end",
+                "start,kj,HN,dc,end",
+                "start,kj,HN,end",
+                "start,kj,dc,HN,end",
+                "start,kj,dc,end",
+            ]
+        );
+        for (data, result) in zip_eq(MOCK_DATA, [103, 3509]) {
+            let map = parse(data).unwrap();
+            assert_eq!(
+                paths(&map, SmallCaves::SINGLE_TWICE).unique().count(),
+                result
+            );
+        }
     }
 }
@@ -2,11 +2,14 @@ use std::collections::HashSet;
 
 use itertools::Itertools;
 
-use crate::loaders::file_to_lines;
+use crate::{
+    loaders::{parse_at, parse_file},
+    Day, Error, Result,
+};
 pub const DATA: &str = "input/aoc13";
 
 pub type Point = [u32; 2];
-#[cfg_attr(test, derive(Clone))]
+#[derive(Clone)]
 pub struct Instruction {
     axis: usize,
     index: u32,
@@ -14,31 +17,72 @@ pub struct Instruction {
 type Points = HashSet<Point>;
 type Instructions = Vec<Instruction>;
 
-pub fn load(filename: impl AsRef<std::path::Path>) -> (Points, Instructions) {
-    let mut lines = file_to_lines(filename);
-    let points = lines
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<(Points, Instructions)> {
+    parse_file(filename, parse)
+}
+
+/// Parse the dots and the fold instructions. There must be at least one fold, and no fold may
+/// mirror a dot past the top or left edge.
+pub fn parse(input: &str) -> Result<(Points, Instructions)> {
+    let mut lines = input.lines();
+    let points: Points = lines
         .by_ref()
         .take_while(|line| !line.is_empty())
-        .map(|line| {
-            let (x, y) = line.split_once(',').unwrap();
-            [x.parse().unwrap(), y.parse().unwrap()]
+        .map(|line| -> Result<Point> {
+            let (x, y) = line
+                .split_once(',')
+                .ok_or_else(|| Error::at(input, line, "Expected a point like `x,y`"))?;
+            Ok([parse_at(input, x)?, parse_at(input, y)?])
         })
-        .collect();
-
-    let instructions = lines
-        .map(|line| match line.split_once('=') {
-            Some(("fold along x", i)) => Instruction {
-                axis: 0,
-                index: i.parse().unwrap(),
-            },
-            Some(("fold along y", i)) => Instruction {
-                axis: 1,
-                index: i.parse().unwrap(),
-            },
-            _ => panic!("unexpected line!\n{}", line),
+        .try_collect()?;
+
+    // Folds along one axis never move dots along the other, so the coordinates of each axis can
+    // be folded on their own to find the dots mirrored too far
+    let mut coordinates: [HashSet<u32>; 2] =
+        [0, 1].map(|axis| points.iter().map(|point| point[axis]).collect());
+    let instructions: Instructions = lines
+        .map(|line| {
+            let (axis, i) = match line.split_once('=') {
+                Some(("fold along x", i)) => (0, i),
+                Some(("fold along y", i)) => (1, i),
+                _ => return Err(Error::at(input, line, "Expected a fold instruction")),
+            };
+            let instruction = Instruction {
+                axis,
+                index: parse_at(input, i)?,
+            };
+            if coordinates[axis]
+                .iter()
+                .any(|&c| c > instruction.index && c - instruction.index > instruction.index)
+            {
+                let reason = "Expected no dots further from the fold than the edge";
+                return Err(Error::at(input, i, reason));
+            }
+            coordinates[axis] = coordinates[axis]
+                .iter()
+                .map(|&c| mirror(c, instruction.index))
+                .collect();
+            Ok(instruction)
         })
-        .collect();
-    (points, instructions)
+        .try_collect()?;
+    if instructions.is_empty() {
+        let end = &input[input.len()..];
+        return Err(Error::at(
+            input,
+            end,
+            "Expected at least one fold instruction",
+        ));
+    }
+    Ok((points, instructions))
+}
+
+/// Mirror a coordinate beyond a fold, parsing ensures it doesn't end up below zero
+fn mirror(coordinate: u32, index: u32) -> u32 {
+    if index < coordinate {
+        index - (coordinate - index)
+    } else {
+        coordinate
+    }
 }
 
 fn fold(points: &mut Points, instruction: &Instruction) {
@@ -48,10 +92,7 @@ fn fold(points: &mut Points, instruction: &Instruction) {
     let folded = points
         .drain()
         .update(|point| {
-            if instruction.index < point[instruction.axis] {
-                // Mirror point
-                point[instruction.axis] = 2 * instruction.index - point[instruction.axis];
-            }
+            point[instruction.axis] = mirror(point[instruction.axis], instruction.index)
         })
         .collect_vec();
     points.extend(folded);
@@ -92,71 +133,89 @@ pub fn answer2((mut points, instructions): (Points, Instructions)) -> String {
     points_to_string(&points)
 }
 
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 13;
+    const DATA: &'static str = DATA;
+
+    type Input = (Points, Instructions);
+    type Answer1 = usize;
+    type Answer2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const MOCK_POINTS: [Point; 18] = [
-        [6, 10],
-        [0, 14],
-        [9, 10],
-        [0, 3],
-        [10, 4],
-        [4, 11],
-        [6, 0],
-        [6, 12],
-        [4, 1],
-        [0, 13],
-        [10, 12],
-        [3, 4],
-        [3, 0],
-        [8, 4],
-        [1, 10],
-        [2, 14],
-        [8, 10],
-        [9, 0],
-    ];
-    const MOCK_INSTRUCTIONS: [Instruction; 2] = [
-        Instruction { axis: 1, index: 7 },
-        Instruction { axis: 0, index: 5 },
-    ];
+    const MOCK_DATA: &str = concat!(
+        "6,10\n",
+        "0,14\n",
+        "9,10\n",
+        "0,3\n",
+        "10,4\n",
+        "4,11\n",
+        "6,0\n",
+        "6,12\n",
+        "4,1\n",
+        "0,13\n",
+        "10,12\n",
+        "3,4\n",
+        "3,0\n",
+        "8,4\n",
+        "1,10\n",
+        "2,14\n",
+        "8,10\n",
+        "9,0\n",
+        "\n",
+        "fold along y=7\n",
+        "fold along x=5\n",
+    );
     const MOCK_SOLUTION: &str = "
 #####
 #   #
 #   #
 #   #
 #####";
-    const SOLUTION: &str = "
-#  #  ##  #    #### ###   ##  #### #  #
-#  # #  # #       # #  # #  #    # #  #
-#  # #    #      #  #  # #  #   #  #  #
-#  # #    #     #   ###  ####  #   #  #
-#  # #  # #    #    # #  #  # #    #  #
- ##   ##  #### #### #  # #  # ####  ##";
 
     #[test]
     fn test_answer1_mock_data() {
-        assert_eq!(
-            answer1((HashSet::from_iter(MOCK_POINTS), MOCK_INSTRUCTIONS.to_vec())),
-            17
-        )
+        assert_eq!(answer1(parse(MOCK_DATA).unwrap()), 17)
     }
 
     #[test]
     fn test_answer2_mock_data() {
-        assert_eq!(
-            answer2((HashSet::from_iter(MOCK_POINTS), MOCK_INSTRUCTIONS.to_vec())),
-            MOCK_SOLUTION
-        )
-    }
-
-    #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(load(DATA)), 693)
+        assert_eq!(answer2(parse(MOCK_DATA).unwrap()), MOCK_SOLUTION)
     }
 
     #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(load(DATA)), SOLUTION)
+    fn test_parse_error() {
+        assert!(parse("").is_err());
+        assert!(parse("6,10\n").is_err());
+        assert!(matches!(
+            parse("6,10\n\nfold along y=4\n").err(),
+            Some(Error::Parse {
+                line: 3,
+                column: 14,
+                ..
+            })
+        ));
+        // The dot is at 2 by the second fold, which a fold at 1 can mirror but one at 0 can't
+        assert!(parse("3,4\n\nfold along y=3\nfold along y=1\n").is_ok());
+        assert!(parse("3,4\n\nfold along y=3\nfold along y=0\n").is_err());
+        let far = format!("{},1\n\nfold along x={}\n", u32::MAX, 1u32 << 31);
+        assert_eq!(answer1(parse(&far).unwrap()), 1);
     }
 }
@@ -1,4 +1,4 @@
-use crate::loaders::file_to_lines;
+use crate::{loaders::parse_file, Day, Error, Result};
 use std::collections::HashMap;
 
 use itertools::Itertools;
@@ -14,25 +14,58 @@ const fn pack_bytes(b1: u8, b2: u8) -> u32 {
     (b1 as u32) << 8 | b2 as u32
 }
 
-pub fn load(filename: impl AsRef<std::path::Path>) -> (Polymer, RuleMap) {
-    let mut lines = file_to_lines(filename);
-    let polymer = lines.next().unwrap().into_bytes(); // Polymer is first line ...
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<(Polymer, RuleMap)> {
+    parse_file(filename, parse)
+}
+
+/// Parse the polymer template and the insertion rules. Every pair of the template, and every pair
+/// a rule inserts, must have a rule of its own.
+pub fn parse(input: &str) -> Result<(Polymer, RuleMap)> {
+    let mut lines = input.lines();
+    let template = lines
+        .next()
+        .filter(|template| !template.is_empty())
+        .ok_or_else(|| Error::at(input, input, "Missing polymer template"))?;
+    if let Some((i, c)) = template.char_indices().find(|(_, c)| !c.is_ascii()) {
+        let element = &template[i..i + c.len_utf8()];
+        return Err(Error::at(input, element, "Expected an ASCII element"));
+    }
+    let polymer = template.as_bytes().to_vec(); // Polymer is first line ...
 
-    let rules = lines
+    let rules: Vec<(&str, u32, [u32; 2])> = lines
         .skip(1) // ... and then a newline ...
         // ... and finally the rules
-        .map(|line| {
-            let (pair, new_byte) = line.split_once(" -> ").unwrap();
-            let [p1, p2]: [u8; 2] = pair.as_bytes().try_into().unwrap();
-            let new_byte = new_byte.bytes().next().unwrap();
-            (
+        .map(|line| -> Result<(&str, u32, [u32; 2])> {
+            let bad_rule = || Error::at(input, line, "Expected a rule like `AB -> C`");
+            let (pair, new_byte) = line.split_once(" -> ").ok_or_else(bad_rule)?;
+            let [p1, p2]: [u8; 2] = pair.as_bytes().try_into().map_err(|_| bad_rule())?;
+            let [new_byte]: [u8; 1] = new_byte.as_bytes().try_into().map_err(|_| bad_rule())?;
+            Ok((
+                line,
                 pack_bytes(p1, p2),
                 [pack_bytes(p1, new_byte), pack_bytes(new_byte, p2)],
-            )
+            ))
         })
+        .try_collect()?;
+    let rule_map: RuleMap = rules
+        .iter()
+        .map(|&(_, pair, new_pairs)| (pair, new_pairs))
         .collect();
 
-    (polymer, rules)
+    // Make sure growing never finds a pair without a rule
+    for (i, pair) in polymer.windows(2).enumerate() {
+        if !rule_map.contains_key(&pack_bytes(pair[0], pair[1])) {
+            let reason = "Expected a rule for every pair of the template";
+            return Err(Error::at(input, &template[i..i + 2], reason));
+        }
+    }
+    for (line, _, new_pairs) in rules {
+        if !new_pairs.iter().all(|pair| rule_map.contains_key(pair)) {
+            let reason = "Expected rules for both pairs made by the rule";
+            return Err(Error::at(input, line, reason));
+        }
+    }
+    Ok((polymer, rule_map))
 }
 
 /// Single iteration of growing the polymer into a new one
@@ -69,12 +102,12 @@ fn answer((polymer, rules): (Polymer, RuleMap), n: u32) -> u64 {
     // ... and make sure to count first symbol also which is otherwise missed
     *counts.entry(polymer[0]).or_default() += 1;
 
-    use itertools::MinMaxResult::*;
-    match counts.into_values().minmax() {
-        MinMax(min, max) => max - min,
-        NoElements => panic!("Polymer is empty"),
-        OneElement(n) => panic!("All characters exist exactly {} time(s)", n),
-    }
+    // A polymer of a single element has a difference of 0
+    counts
+        .into_values()
+        .minmax()
+        .into_option()
+        .map_or(0, |(min, max)| max - min)
 }
 
 pub fn answer1(input: (Polymer, RuleMap)) -> u64 {
@@ -85,98 +118,90 @@ pub fn answer2(input: (Polymer, RuleMap)) -> u64 {
     answer(input, 40)
 }
 
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 14;
+    const DATA: &'static str = DATA;
+
+    type Input = (Polymer, RuleMap);
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const MOCK_POLYMER: [u8; 4] = [b'N', b'N', b'C', b'B'];
-    const MOCK_RULES: [(u32, [u32; 2]); 16] = [
-        (
-            pack_bytes(b'C', b'H'),
-            [pack_bytes(b'C', b'B'), pack_bytes(b'B', b'H')],
-        ),
-        (
-            pack_bytes(b'H', b'H'),
-            [pack_bytes(b'H', b'N'), pack_bytes(b'N', b'H')],
-        ),
-        (
-            pack_bytes(b'C', b'B'),
-            [pack_bytes(b'C', b'H'), pack_bytes(b'H', b'B')],
-        ),
-        (
-            pack_bytes(b'N', b'H'),
-            [pack_bytes(b'N', b'C'), pack_bytes(b'C', b'H')],
-        ),
-        (
-            pack_bytes(b'H', b'B'),
-            [pack_bytes(b'H', b'C'), pack_bytes(b'C', b'B')],
-        ),
-        (
-            pack_bytes(b'H', b'C'),
-            [pack_bytes(b'H', b'B'), pack_bytes(b'B', b'C')],
-        ),
-        (
-            pack_bytes(b'H', b'N'),
-            [pack_bytes(b'H', b'C'), pack_bytes(b'C', b'N')],
-        ),
-        (
-            pack_bytes(b'N', b'N'),
-            [pack_bytes(b'N', b'C'), pack_bytes(b'C', b'N')],
-        ),
-        (
-            pack_bytes(b'B', b'H'),
-            [pack_bytes(b'B', b'H'), pack_bytes(b'H', b'H')],
-        ),
-        (
-            pack_bytes(b'N', b'C'),
-            [pack_bytes(b'N', b'B'), pack_bytes(b'B', b'C')],
-        ),
-        (
-            pack_bytes(b'N', b'B'),
-            [pack_bytes(b'N', b'B'), pack_bytes(b'B', b'B')],
-        ),
-        (
-            pack_bytes(b'B', b'N'),
-            [pack_bytes(b'B', b'B'), pack_bytes(b'B', b'N')],
-        ),
-        (
-            pack_bytes(b'B', b'B'),
-            [pack_bytes(b'B', b'N'), pack_bytes(b'N', b'B')],
-        ),
-        (
-            pack_bytes(b'B', b'C'),
-            [pack_bytes(b'B', b'B'), pack_bytes(b'B', b'C')],
-        ),
-        (
-            pack_bytes(b'C', b'C'),
-            [pack_bytes(b'C', b'N'), pack_bytes(b'N', b'C')],
-        ),
-        (
-            pack_bytes(b'C', b'N'),
-            [pack_bytes(b'C', b'C'), pack_bytes(b'C', b'N')],
-        ),
-    ];
+    const MOCK_DATA: &str = concat!(
+        "NNCB\n",
+        "\n",
+        "CH -> B\n",
+        "HH -> N\n",
+        "CB -> H\n",
+        "NH -> C\n",
+        "HB -> C\n",
+        "HC -> B\n",
+        "HN -> C\n",
+        "NN -> C\n",
+        "BH -> H\n",
+        "NC -> B\n",
+        "NB -> B\n",
+        "BN -> B\n",
+        "BB -> N\n",
+        "BC -> B\n",
+        "CC -> N\n",
+        "CN -> C\n",
+    );
 
     #[test]
     fn test_answer1_mock_data() {
-        assert_eq!(answer1((MOCK_POLYMER.to_vec(), MOCK_RULES.into())), 1588)
+        assert_eq!(answer1(parse(MOCK_DATA).unwrap()), 1588)
     }
 
     #[test]
     fn test_answer2_mock_data() {
-        assert_eq!(
-            answer2((MOCK_POLYMER.to_vec(), MOCK_RULES.into())),
-            2188189693529
-        )
+        assert_eq!(answer2(parse(MOCK_DATA).unwrap()), 2188189693529)
     }
 
     #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(load(DATA)), 2194)
+    fn test_single_element() {
+        assert_eq!(answer1(parse("0\n").unwrap()), 0);
+        assert_eq!(answer1(parse("NN\n\nNN -> N\n").unwrap()), 0);
     }
 
     #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(load(DATA)), 2360298895777)
+    fn test_parse_error() {
+        assert!(matches!(
+            parse(&MOCK_DATA.replace("NNCB", "NNCBA")).err(),
+            Some(Error::Parse {
+                line: 1,
+                column: 4,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse(&MOCK_DATA.replace("NH -> C", "NH -> X")).err(),
+            Some(Error::Parse {
+                line: 6,
+                column: 1,
+                ..
+            })
+        ));
+        assert!(parse("abc").is_err());
+        assert!(parse("").is_err());
+        assert!(parse("NÑ\n").is_err());
     }
 }
@@ -1,109 +1,82 @@
-pub use crate::loaders::file_to_squashed_2d_vec as load;
-use itertools::{iproduct, Itertools};
-use std::{cmp::Reverse, collections::BinaryHeap, iter::repeat};
+use crate::{
+    grid::{Grid, Point},
+    loaders::parse_file,
+    search::{self, manhattan, Path},
+    Day, Error, Result,
+};
+use itertools::Itertools;
 
 pub const DATA: &str = "input/aoc15";
 
 type Node = u32;
-type Cave = Vec<Node>;
-type Neighbours = Vec<Vec<usize>>;
-
-/// Lookup table for neighbours, saves time at the cost of memory
-fn bake_neighbour_lookup(n_rows: usize, n_cols: usize) -> Neighbours {
-    let max_row = n_rows - 1;
-    let max_col = n_cols - 1;
-    iproduct!(0..n_rows, 0..n_cols)
-        .map(|(row, col)| {
-            let idx = n_cols * row + col;
-            let mut out = Vec::with_capacity(4);
-            if row > 0 {
-                out.push(idx - n_cols);
-            }
-            if row < max_row {
-                out.push(idx + n_cols);
-            }
-            if col > 0 {
-                out.push(idx - 1);
-            }
-            if col < max_col {
-                out.push(idx + 1);
-            }
-            out
-        })
-        .collect()
+type Cave = Grid<Node>;
+
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<Cave> {
+    parse_file(filename, parse)
 }
 
-fn a_star(nodes: &Cave, width: usize, start: usize, goal: usize) -> u32 {
-    let n_nodes = nodes.len();
-    let height = n_nodes / width;
-    let m_distance = iproduct!(0..height, 0..width)
-        .map(|(r, c)| (height - r + width - c) as u32)
-        .collect_vec();
-    let neighbours = bake_neighbour_lookup(height, width);
-
-    let mut g_score = repeat(u32::MAX).take(n_nodes).collect_vec();
-    let mut closed = repeat(false).take(n_nodes).collect_vec();
-
-    g_score[start] = 0;
-    let mut open = BinaryHeap::from([(Reverse(m_distance[start]), start)]);
-    loop {
-        let (_, current) = open.pop().unwrap();
-
-        if current == goal {
-            return g_score[goal];
-        }
-        if closed[current] {
-            continue;
-        }
-
-        for &nbor in &neighbours[current] {
-            if closed[nbor] {
-                continue;
-            }
-            let candidate_g_score = g_score[current] + nodes[nbor];
-            if candidate_g_score < g_score[nbor] {
-                g_score[nbor] = candidate_g_score;
-                open.push((Reverse(candidate_g_score + m_distance[nbor]), nbor));
-            }
-        }
-        closed[current] = true;
-    }
+/// Parse the risk levels, which are at least 1 so that the distance heuristic never overestimates
+pub fn parse(input: &str) -> Result<Cave> {
+    Grid::parse_with(input, |c| match c.to_digit(10) {
+        Some(0) => Err("Expected a risk level of 1 to 9"),
+        Some(risk) => Ok(risk),
+        None => Err("Expected a digit"),
+    })
 }
 
-fn extend(cave: Cave, width: usize, n: usize) -> Cave {
-    let mut new_cave = Vec::with_capacity(cave.len() * n * n);
-    // Copy to left
-    for row in cave.chunks(width) {
-        for _ in 0..n {
-            new_cave.extend_from_slice(row);
-        }
-    }
-    // Copy down
-    for _ in 0..n - 1 {
-        new_cave.extend_from_within(..n * cave.len());
-    }
-    // Increase
-    for (i_row, row) in new_cave.chunks_mut(n * cave.len()).enumerate() {
-        let i_row = i_row as u32;
-        for (i_col, seg) in row.chunks_mut(width).enumerate() {
-            let i_col = (i_col % n) as u32;
-            for num in seg {
-                *num = (*num + i_row + i_col - 1) % 9 + 1;
-            }
-        }
-    }
+/// Path of lowest total risk from the top left to the bottom right corner of the cave
+pub fn lowest_risk_path(cave: &Cave) -> Result<Path<Point, u32>> {
+    let goal = (cave.height() - 1, cave.width() - 1);
+    search::a_star(
+        cave,
+        (0, 0),
+        |point| point == goal,
+        |point| manhattan(point, goal) as u32,
+    )
+    .ok_or_else(|| Error::solve("The bottom right corner can't be reached"))
+}
 
+/// Tile the cave `n` times in each direction, increasing the risk by one per tile step
+fn extend(cave: Cave, n: usize) -> Cave {
+    let (height, width) = (cave.height(), cave.width());
+    let mut new_cave = Grid::new(n * width, n * height, 0);
+    for (row, col) in new_cave.points().collect_vec() {
+        let increase = (row / height + col / width) as Node;
+        new_cave[(row, col)] = (cave[(row % height, col % width)] + increase - 1) % 9 + 1;
+    }
     new_cave
 }
 
-pub fn answer1((cave, width): (Cave, usize)) -> u32 {
-    a_star(&cave, width, 0, cave.len() - 1)
+pub fn answer1(cave: Cave) -> Result<u32> {
+    Ok(lowest_risk_path(&cave)?.cost)
+}
+
+pub fn answer2(cave: Cave) -> Result<u32> {
+    let cave = extend(cave, 5);
+    Ok(lowest_risk_path(&cave)?.cost)
 }
 
-pub fn answer2((cave, mut width): (Cave, usize)) -> u32 {
-    let cave = extend(cave, width, 5);
-    width *= 5;
-    a_star(&cave, width, 0, cave.len() - 1)
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 15;
+    const DATA: &'static str = DATA;
+
+    type Input = Cave;
+    type Answer1 = Result<u32>;
+    type Answer2 = Result<u32>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(input)
+    }
 }
 
 #[cfg(test)]
@@ -127,21 +100,39 @@ mod tests {
 
     #[test]
     fn test_answer1_mock_data() {
-        assert_eq!(answer1((MOCK_DATA.to_vec(), MOCK_WIDTH)), 40)
+        assert_eq!(
+            answer1(Grid::from_vec(MOCK_DATA.to_vec(), MOCK_WIDTH)).unwrap(),
+            40
+        )
     }
 
     #[test]
     fn test_answer2_mock_data() {
-        assert_eq!(answer2((MOCK_DATA.to_vec(), MOCK_WIDTH)), 315)
+        assert_eq!(
+            answer2(Grid::from_vec(MOCK_DATA.to_vec(), MOCK_WIDTH)).unwrap(),
+            315
+        )
     }
 
     #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(load(DATA)), 619)
+    fn test_lowest_risk_path() {
+        let cave = Grid::from_vec(MOCK_DATA.to_vec(), MOCK_WIDTH);
+        let path = lowest_risk_path(&cave).unwrap();
+        assert_eq!(path.nodes.len(), 19); // Only steps right and down
+        assert_eq!(path.nodes[1..].iter().map(|&p| cave[p]).sum::<u32>(), 40);
     }
 
     #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(load(DATA)), 2922)
+    fn test_parse_error() {
+        assert_eq!(parse("116\n138\n").unwrap().len(), 6);
+        assert!(matches!(
+            parse("116\n308\n").err(),
+            Some(Error::Parse {
+                line: 2,
+                column: 2,
+                ..
+            })
+        ));
+        assert!(parse("1x\n").is_err());
     }
 }
@@ -1,159 +1,830 @@
-use crate::loaders::file_to_string;
-use bits::Bits;
+use crate::{loaders::parse_file, Day, Error, Result};
+pub use bits::Bits;
+use itertools::Itertools;
+use num_bigint::BigUint;
 
 pub const DATA: &str = "input/aoc16";
 
-pub fn load(data: impl AsRef<std::path::Path>) -> Bits {
-    file_to_string(data).trim().parse().unwrap()
+pub fn load(data: impl AsRef<std::path::Path>) -> Result<Packet> {
+    parse_file(data, parse)
 }
+
+pub fn parse(input: &str) -> Result<Packet> {
+    let data = input.trim();
+    let to_error = |err| locate(input, data, err);
+    let mut bits: Bits = data.parse().map_err(to_error)?;
+    let packet = Packet::decode(&mut bits).map_err(to_error)?;
+    bits.check_padding().map_err(to_error)?;
+    Ok(packet)
+}
+
+/// An error found at a bit offset from the start of a transmission
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    pub offset: usize,
+    pub reason: String,
+}
+
+impl DecodeError {
+    fn new(offset: usize, reason: impl std::fmt::Display) -> Self {
+        Self {
+            offset,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "bit {}: {}", self.offset, self.reason)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// An operator built with a type id which isn't one, or with the wrong number of sub-packets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidOperator {
+    pub type_id: u8,
+    pub reason: String,
+}
+
+impl std::fmt::Display for InvalidOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl std::error::Error for InvalidOperator {}
+
+/// A packet built with a version which doesn't fit in the 3 bits of its header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidVersion(pub u8);
+
+impl std::fmt::Display for InvalidVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Expected a version below 8, found {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidVersion {}
+
+/// Point at the hex digit of `data` holding the bit of the error, or at the end of `data` if the
+/// transmission was cut short
+fn locate(input: &str, data: &str, err: DecodeError) -> Error {
+    let digit = data
+        .char_indices()
+        .nth(err.offset / 4)
+        .map_or(&data[data.len()..], |(i, c)| &data[i..i + c.len_utf8()]);
+    Error::at(input, digit, err)
+}
+
 mod bits {
+    use super::DecodeError;
     use bitvec::prelude::*;
     use funty::Integral;
-    use itertools::{Either, Itertools};
-    use std::iter;
+    use itertools::Itertools;
+    use num_bigint::BigUint;
 
+    #[derive(Clone, Default)]
     pub struct Bits {
         data: BitVec<u8, Msb0>, // Use bytes since input data is in full bytes, use Msb0 since it makes loading the BitVec easier
         idx: usize,
     }
 
     impl std::str::FromStr for Bits {
-        type Err = std::num::ParseIntError;
+        type Err = DecodeError;
 
         fn from_str(data: &str) -> Result<Self, Self::Err> {
-            let n = data.len();
-            let data = (0..n)
-                .step_by(2) // 2 hex make a byte
-                .map(|i| u8::from_str_radix(&data[i..i + 2], 16))
+            let digits: Vec<u8> = data
+                .chars()
+                .enumerate()
+                .map(|(i, c)| {
+                    let reason = || format!("Expected a hex digit, found `{}`", c);
+                    c.to_digit(16)
+                        .map(|digit| digit as u8)
+                        .ok_or_else(|| DecodeError::new(4 * i, reason()))
+                })
                 .try_collect()?;
+            if !digits.len().is_multiple_of(2) {
+                let reason = format!(
+                    "Expected an even number of hex digits, found {}",
+                    digits.len()
+                );
+                return Err(DecodeError::new(4 * digits.len(), reason));
+            }
+            let data = digits
+                .chunks(2) // 2 hex make a byte
+                .map(|pair| pair[0] << 4 | pair[1])
+                .collect();
             Ok(Self { data, idx: 0 })
         }
     }
 
+    /// Hex with the last byte padded with zeros
+    impl std::fmt::Display for Bits {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            let mut data = self.data.clone();
+            data.resize(self.data.len().next_multiple_of(8), false);
+            data.as_raw_slice()
+                .iter()
+                .try_for_each(|byte| write!(f, "{:02X}", byte))
+        }
+    }
+
     impl Bits {
-        /// Move the index forward by `n` places
-        pub fn skip(&mut self, n: usize) {
-            self.idx += n;
+        /// The total number of bits
+        pub fn len(&self) -> usize {
+            self.data.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.data.is_empty()
+        }
+
+        /// Append a single bit at the end
+        pub fn push(&mut self, bit: bool) {
+            self.data.push(bit);
+        }
+
+        /// Append the lowest `len` bits of an integer at the end
+        pub fn push_integral<I: Integral>(&mut self, int: I, len: usize) {
+            let start = self.data.len();
+            self.data.resize(start + len, false);
+            self.data[start..].store_be(int);
+        }
+
+        /// The index of the next bit to be read
+        pub fn position(&self) -> usize {
+            self.idx
+        }
+
+        /// Get the next `len` bits and move the index past them
+        fn take(&mut self, len: usize) -> Result<&BitSlice<u8, Msb0>, DecodeError> {
+            let remaining = self.data.len() - self.idx;
+            if len > remaining {
+                let reason = format!("Expected {} more bits, found {}", len, remaining);
+                return Err(DecodeError::new(self.data.len(), reason));
+            }
+            self.idx += len;
+            Ok(&self.data[self.idx - len..self.idx])
         }
 
         /// Get the next bit as a `bool`
-        pub fn pop(&mut self) -> bool {
-            let b = self.data[self.idx];
-            self.skip(1);
-            b
+        pub fn pop(&mut self) -> Result<bool, DecodeError> {
+            Ok(self.take(1)?[0])
         }
 
         /// Get the next len bits as an integer
-        pub fn pop_integral<I: Integral>(&mut self, len: usize) -> I {
-            let int = self.data[self.idx..self.idx + len].load_be();
-            self.skip(len);
-            int
-        }
-
-        /// Get the length of the literal packet which is currently at the pointer. No checks are made.
-        pub fn get_literal_len(&self) -> usize {
-            (self.idx..)
-                .step_by(5) // Each block in the literal packet is 5 bits long
-                .take_while(|&idx| self.data[idx])
-                .count()
-                * 5
-                + 5
-        }
-
-        /// Pop of the next literal from memory starting from the current index
-        pub fn pop_literal<I>(&mut self) -> I
-        where
-            I: Integral,
-        {
-            let len = self.get_literal_len();
-            let int = self.data[self.idx..self.idx + len]
-                .chunks(5)
-                .fold(I::default(), |val, new| {
-                    (val << 4) | new[1..].load_be::<I>()
-                });
-            self.skip(len);
-            int
-        }
-
-        /// Iterate over the content of the operator by repeatably applying the closure to `self` a
-        /// limited number of times. The limit is decided by the contents of the operator packet
-        pub fn iter_operator<'a, F, I>(
-            &'a mut self,
-            fun: F,
-        ) -> Either<impl Iterator<Item = I> + 'a, impl Iterator<Item = I> + 'a>
-        where
-            F: Fn(&mut Bits) -> I + 'a,
-            I: Integral,
+        pub fn pop_integral<I: Integral>(&mut self, len: usize) -> Result<I, DecodeError> {
+            Ok(self.take(len)?.load_be())
+        }
+
+        /// Pop of the next literal from memory starting from the current index, however long
+        pub fn pop_literal(&mut self) -> Result<BigUint, DecodeError> {
+            let mut int = BigUint::default();
+            loop {
+                // Each group is a bit telling if more groups follow, and then 4 bits of the value
+                let more = self.pop()?;
+                int = (int << 4u8) | BigUint::from(self.pop_integral::<u8>(4)?);
+                if !more {
+                    return Ok(int);
+                }
+            }
+        }
+
+        /// Check that the bits after the index are only zeros padding the transmission
+        pub fn check_padding(&self) -> Result<(), DecodeError> {
+            match self.data[self.idx..].first_one() {
+                Some(one) => Err(DecodeError::new(
+                    self.idx + one,
+                    "Expected only zeros after the packet, found a 1",
+                )),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+/// How the length of the children of an operator packet is given
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthType {
+    /// Length id 0, the total number of bits in the children
+    Bits,
+    /// Length id 1, the number of child packets
+    Packets,
+}
+
+/// A decoded packet, with all its sub-packets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    Literal {
+        version: u8,
+        value: BigUint,
+    },
+    Operator {
+        version: u8,
+        type_id: u8,
+        length_type: LengthType,
+        children: Vec<Packet>,
+    },
+}
+
+/// The header of a packet as found in a transmission
+struct Header {
+    /// Bit offset of the packet from the start of the transmission
+    offset: usize,
+    /// Number of operators the packet is nested within
+    depth: usize,
+    version: u8,
+    type_id: u8,
+    content: Content,
+}
+
+enum Content {
+    Literal(BigUint),
+    /// The length type and length of the children of an operator
+    Operator(LengthType, usize),
+}
+
+// Packet type ids, every type id other than `LITERAL` is an operator
+const SUM: u8 = 0;
+const PRODUCT: u8 = 1;
+const MINIMUM: u8 = 2;
+const MAXIMUM: u8 = 3;
+const LITERAL: u8 = 4;
+const GREATER_THAN: u8 = 5;
+const LESS_THAN: u8 = 6;
+const EQUAL_TO: u8 = 7;
+
+/// Number of bits used for the length of the children, for each length type
+const BITS_LEN: usize = 15;
+const PACKETS_LEN: usize = 11;
+
+/// Deepest nesting of operators accepted when decoding, keeping the recursion off the end of the
+/// stack for malicious transmissions
+const MAX_DEPTH: usize = 100;
+
+impl Packet {
+    /// Decode the packet starting at the current index, leaving the index right after it
+    pub fn decode(bits: &mut Bits) -> Result<Self, DecodeError> {
+        Self::decode_listed(bits, 0, &mut |_| {})
+    }
+
+    /// Decode a packet while reporting the header of it and each of its sub-packets, in the order
+    /// they appear in the transmission
+    fn decode_listed(
+        bits: &mut Bits,
+        depth: usize,
+        list: &mut impl FnMut(Header),
+    ) -> Result<Self, DecodeError> {
+        let offset = bits.position();
+        if depth > MAX_DEPTH {
+            let reason = format!("Packets nested deeper than {}", MAX_DEPTH);
+            return Err(DecodeError::new(offset, reason));
+        }
+        let version = bits.pop_integral(3)?;
+        let type_id = bits.pop_integral(3)?;
+        let header = |content| Header {
+            offset,
+            depth,
+            version,
+            type_id,
+            content,
+        };
+        if type_id == LITERAL {
+            let value = bits.pop_literal()?;
+            list(header(Content::Literal(value.clone())));
+            return Ok(Packet::Literal { version, value });
+        }
+        let (length_type, children) = if bits.pop()? {
+            let n: usize = bits.pop_integral(PACKETS_LEN)?;
+            list(header(Content::Operator(LengthType::Packets, n)));
+            let children = (0..n)
+                .map(|_| Packet::decode_listed(bits, depth + 1, list))
+                .try_collect()?;
+            (LengthType::Packets, children)
+        } else {
+            let len: usize = bits.pop_integral(BITS_LEN)?;
+            list(header(Content::Operator(LengthType::Bits, len)));
+            let end = bits.position() + len;
+            let mut children = Vec::new();
+            while bits.position() < end {
+                children.push(Packet::decode_listed(bits, depth + 1, list)?);
+            }
+            if bits.position() != end {
+                let reason = format!(
+                    "Expected the sub-packets to end at bit {}, found them ending at bit {}",
+                    end,
+                    bits.position()
+                );
+                return Err(DecodeError::new(end, reason));
+            }
+            (LengthType::Bits, children)
+        };
+        check_operator(type_id, &children).map_err(|reason| DecodeError::new(offset, reason))?;
+        Ok(Packet::Operator {
+            version,
+            type_id,
+            length_type,
+            children,
+        })
+    }
+
+    pub fn version(&self) -> u8 {
+        match self {
+            Packet::Literal { version, .. } | Packet::Operator { version, .. } => *version,
+        }
+    }
+
+    pub fn type_id(&self) -> u8 {
+        match self {
+            Packet::Literal { .. } => LITERAL,
+            Packet::Operator { type_id, .. } => *type_id,
+        }
+    }
+
+    /// The sub-packets of an operator, literals have none
+    pub fn children(&self) -> &[Packet] {
+        match self {
+            Packet::Literal { .. } => &[],
+            Packet::Operator { children, .. } => children,
+        }
+    }
+
+    /// Reduce the tree bottom up, `fun` is given each packet together with the results of its
+    /// children
+    pub fn fold<T>(&self, fun: &mut impl FnMut(&Packet, Vec<T>) -> T) -> T {
+        let results = self
+            .children()
+            .iter()
+            .map(|child| child.fold(fun))
+            .collect();
+        fun(self, results)
+    }
+}
+
+/// Building and encoding packets. Built packets have version 0, and operators use the 15 bit
+/// length of their children unless it doesn't fit.
+impl Packet {
+    pub fn literal(value: impl Into<BigUint>) -> Self {
+        Packet::Literal {
+            version: 0,
+            value: value.into(),
+        }
+    }
+
+    pub fn operator(type_id: u8, children: Vec<Packet>) -> Result<Self, InvalidOperator> {
+        check_operator(type_id, &children)
+            .and_then(|()| {
+                // Too many sub-packets to count, so their bits must fit instead
+                if children.len() < 1 << PACKETS_LEN {
+                    Ok(())
+                } else {
+                    check_length(LengthType::Bits, &children)
+                }
+            })
+            .map_err(|reason| InvalidOperator { type_id, reason })?;
+        Ok(Self::checked_operator(type_id, children))
+    }
+
+    /// Build an operator which is known to have a valid type id and number of sub-packets, and
+    /// sub-packets which fit either length type
+    fn checked_operator(type_id: u8, children: Vec<Packet>) -> Self {
+        let len: usize = children.iter().map(Packet::bit_len).sum();
+        let length_type = if len < 1 << BITS_LEN {
+            LengthType::Bits
+        } else {
+            LengthType::Packets
+        };
+        Packet::Operator {
+            version: 0,
+            type_id,
+            length_type,
+            children,
+        }
+    }
+
+    pub fn sum(children: Vec<Packet>) -> Result<Self, InvalidOperator> {
+        Self::operator(SUM, children)
+    }
+
+    pub fn product(children: Vec<Packet>) -> Result<Self, InvalidOperator> {
+        Self::operator(PRODUCT, children)
+    }
+
+    pub fn minimum(children: Vec<Packet>) -> Result<Self, InvalidOperator> {
+        Self::operator(MINIMUM, children)
+    }
+
+    pub fn maximum(children: Vec<Packet>) -> Result<Self, InvalidOperator> {
+        Self::operator(MAXIMUM, children)
+    }
+
+    pub fn greater_than(a: Packet, b: Packet) -> Self {
+        Self::checked_operator(GREATER_THAN, vec![a, b])
+    }
+
+    pub fn less_than(a: Packet, b: Packet) -> Self {
+        Self::checked_operator(LESS_THAN, vec![a, b])
+    }
+
+    pub fn equal_to(a: Packet, b: Packet) -> Self {
+        Self::checked_operator(EQUAL_TO, vec![a, b])
+    }
+
+    pub fn with_version(mut self, version: u8) -> Result<Self, InvalidVersion> {
+        if version >= 8 {
+            return Err(InvalidVersion(version));
+        }
+        match &mut self {
+            Packet::Literal { version: v, .. } | Packet::Operator { version: v, .. } => {
+                *v = version
+            }
+        }
+        Ok(self)
+    }
+
+    /// Override the length type of an operator, literals are left as they are. Fails if the
+    /// sub-packets don't fit the length type.
+    pub fn with_length_type(mut self, length_type: LengthType) -> Result<Self, InvalidOperator> {
+        if let Packet::Operator {
+            type_id,
+            length_type: l,
+            children,
+            ..
+        } = &mut self
         {
-            if self.pop() {
-                // len id is 1
-                let n = self.pop_integral(11);
-                Either::Left(iter::repeat_with(move || fun(self)).take(n))
-            } else {
-                // len id is 0
-                let bits_to_consume: usize = self.pop_integral(15);
-                let target_idx = self.idx + bits_to_consume;
-                Either::Right((0..).map_while(move |_| {
-                    if self.idx < target_idx {
-                        Some(fun(self))
-                    } else {
-                        None
+            check_length(length_type, children).map_err(|reason| InvalidOperator {
+                type_id: *type_id,
+                reason,
+            })?;
+            *l = length_type;
+        }
+        Ok(self)
+    }
+
+    /// The number of bits the packet takes up once encoded
+    pub fn bit_len(&self) -> usize {
+        match self {
+            Packet::Literal { value, .. } => 6 + 5 * literal_groups(value),
+            Packet::Operator {
+                length_type,
+                children,
+                ..
+            } => {
+                let len = match length_type {
+                    LengthType::Bits => BITS_LEN,
+                    LengthType::Packets => PACKETS_LEN,
+                };
+                7 + len + children.iter().map(Packet::bit_len).sum::<usize>()
+            }
+        }
+    }
+
+    /// Append the packet at the end of `bits`. Panics if the children don't fit the length type.
+    pub fn encode(&self, bits: &mut Bits) {
+        bits.push_integral(self.version(), 3);
+        bits.push_integral(self.type_id(), 3);
+        match self {
+            Packet::Literal { value, .. } => {
+                let groups = value.to_radix_be(16);
+                for (i, &group) in groups.iter().enumerate() {
+                    bits.push(i + 1 < groups.len()); // Every group but the last is prefixed with a 1
+                    bits.push_integral(group, 4);
+                }
+            }
+            Packet::Operator {
+                length_type,
+                children,
+                ..
+            } => {
+                let (id, len, max_len) = match length_type {
+                    LengthType::Bits => {
+                        let len = children.iter().map(Packet::bit_len).sum();
+                        (false, len, BITS_LEN)
                     }
-                }))
+                    LengthType::Packets => (true, children.len(), PACKETS_LEN),
+                };
+                assert!(
+                    len < 1 << max_len,
+                    "Length {} doesn't fit in {} bits",
+                    len,
+                    max_len
+                );
+                bits.push(id);
+                bits.push_integral(len, max_len);
+                for child in children {
+                    child.encode(bits);
+                }
             }
         }
     }
+
+    /// Encode the packet as a hex transmission
+    pub fn to_hex(&self) -> String {
+        let mut bits = Bits::default();
+        self.encode(&mut bits);
+        bits.to_string()
+    }
 }
 
-fn operate(op_code: u32, mut iter: impl Iterator<Item = u64>) -> u64 {
-    match op_code {
-        0 => iter.sum(),
-        1 => iter.product(),
-        2 => iter.min().unwrap(),
-        3 => iter.max().unwrap(),
-        5 => (iter.next().unwrap() > iter.next().unwrap()) as u64,
-        6 => (iter.next().unwrap() < iter.next().unwrap()) as u64,
-        7 => (iter.next().unwrap() == iter.next().unwrap()) as u64,
-        _ => unreachable!(),
+/// The number of 4 bit groups needed to encode a literal value
+fn literal_groups(value: &BigUint) -> usize {
+    (value.bits() as usize).div_ceil(4).max(1)
+}
+
+/// Check that an operator exists for the type id, and that it has the right number of sub-packets
+fn check_operator(type_id: u8, children: &[Packet]) -> Result<(), String> {
+    let name = type_name(type_id);
+    match type_id {
+        SUM | PRODUCT | MINIMUM | MAXIMUM if children.is_empty() => Err(format!(
+            "Expected at least 1 sub-packet for `{}`, found 0",
+            name
+        )),
+        GREATER_THAN | LESS_THAN | EQUAL_TO if children.len() != 2 => Err(format!(
+            "Expected 2 sub-packets for `{}`, found {}",
+            name,
+            children.len()
+        )),
+        SUM | PRODUCT | MINIMUM | MAXIMUM | GREATER_THAN | LESS_THAN | EQUAL_TO => Ok(()),
+        _ => Err(format!("Expected an operator type id, found {}", type_id)),
     }
 }
 
-fn extract_versions(bits: &mut Bits) -> u32 {
-    let version = bits.pop_integral(3);
-    let packet_type: u32 = bits.pop_integral(3);
-    if packet_type == 4 {
-        // Literal, skip past it since we don't care about the content yet
-        bits.skip(bits.get_literal_len());
-        version
+/// Check that the sub-packets of an operator fit in the length field of a length type
+fn check_length(length_type: LengthType, children: &[Packet]) -> Result<(), String> {
+    let (len, max_len, unit) = match length_type {
+        LengthType::Bits => (
+            children.iter().map(Packet::bit_len).sum(),
+            BITS_LEN,
+            "bits of sub-packets",
+        ),
+        LengthType::Packets => (children.len(), PACKETS_LEN, "sub-packets"),
+    };
+    if len < 1 << max_len {
+        Ok(())
     } else {
-        // Operator, apply function recursively
-        version + bits.iter_operator(extract_versions).sum::<u32>()
+        Err(format!(
+            "Expected fewer than {} {}, found {}",
+            1usize << max_len,
+            unit,
+            len
+        ))
     }
 }
 
-fn calculate_operation(bits: &mut Bits) -> u64 {
-    bits.skip(3); // Skip version
-    let packet_type: u32 = bits.pop_integral(3);
-    if packet_type == 4 {
-        // Literal
-        bits.pop_literal()
-    } else {
-        // Operator
-        operate(packet_type, bits.iter_operator(calculate_operation))
+/// Short name of the operation of a type id
+fn type_name(type_id: u8) -> &'static str {
+    match type_id {
+        SUM => "sum",
+        PRODUCT => "product",
+        MINIMUM => "min",
+        MAXIMUM => "max",
+        LITERAL => "literal",
+        GREATER_THAN => "gt",
+        LESS_THAN => "lt",
+        EQUAL_TO => "eq",
+        _ => "unknown",
+    }
+}
+
+/// The packet as an expression, like `(sum 3 (max 7 8))`
+impl std::fmt::Display for Packet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Packet::Literal { value, .. } => write!(f, "{}", value),
+            Packet::Operator {
+                type_id, children, ..
+            } => {
+                write!(f, "({}", type_name(*type_id))?;
+                for child in children {
+                    write!(f, " {}", child)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// List every packet of a hex transmission on its own line, indented by depth, with its bit
+/// offset, version, type and length
+pub fn disassemble(input: &str) -> Result<String> {
+    let data = input.trim();
+    let to_error = |err| locate(input, data, err);
+    let mut bits: Bits = data.parse().map_err(to_error)?;
+    let mut lines = Vec::new();
+    Packet::decode_listed(&mut bits, 0, &mut |header| lines.push(header)).map_err(to_error)?;
+    bits.check_padding().map_err(to_error)?;
+    Ok(lines
+        .into_iter()
+        .map(|header| {
+            let details = match header.content {
+                Content::Literal(value) => format!("= {}", value),
+                Content::Operator(LengthType::Bits, len) => format!("length id 0, {} bits", len),
+                Content::Operator(LengthType::Packets, n) => {
+                    format!("length id 1, {} packets", n)
+                }
+            };
+            format!(
+                "{:>6}: {:indent$}v{} {} {}",
+                header.offset,
+                "",
+                header.version,
+                type_name(header.type_id),
+                details,
+                indent = 2 * header.depth
+            )
+        })
+        .join("\n"))
+}
+
+/// A type packets can be evaluated to
+pub trait Value: Clone + Ord {
+    /// The literal as this type, unless it's too large
+    fn from_literal(value: &BigUint) -> Option<Self>;
+    fn from_bool(b: bool) -> Self;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+}
+
+/// Implement `Value` for integer types, which can't be done for all `Integral` at once since
+/// `BigUint` could implement it as well
+macro_rules! impl_value {
+    ($($int:ty),*) => {$(
+        impl Value for $int {
+            fn from_literal(value: &BigUint) -> Option<Self> {
+                value.try_into().ok()
+            }
+
+            fn from_bool(b: bool) -> Self {
+                b as $int
+            }
+
+            fn checked_add(&self, other: &Self) -> Option<Self> {
+                funty::Integral::checked_add(*self, *other)
+            }
+
+            fn checked_mul(&self, other: &Self) -> Option<Self> {
+                funty::Integral::checked_mul(*self, *other)
+            }
+        }
+    )*};
+}
+
+impl_value!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl Value for BigUint {
+    fn from_literal(value: &BigUint) -> Option<Self> {
+        Some(value.clone())
+    }
+
+    fn from_bool(b: bool) -> Self {
+        BigUint::from(b as u8)
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+}
+
+/// Evaluating a packet gave a value too large for the type it was evaluated to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overflow {
+    /// The packet which overflowed, as an expression
+    pub expression: String,
+    pub type_name: &'static str,
+}
+
+impl std::fmt::Display for Overflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} overflows {}", self.expression, self.type_name)
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+/// Evaluating a packet failed, either on overflow or on an operator which was built by hand with
+/// an invalid type id or number of sub-packets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvaluateError {
+    Overflow(Overflow),
+    InvalidOperator(InvalidOperator),
+}
+
+impl std::fmt::Display for EvaluateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvaluateError::Overflow(err) => write!(f, "{}", err),
+            EvaluateError::InvalidOperator(err) => write!(f, "{}", err),
+        }
     }
 }
 
-pub fn answer1(mut input: Bits) -> u32 {
-    extract_versions(&mut input)
+impl std::error::Error for EvaluateError {}
+
+impl From<Overflow> for EvaluateError {
+    fn from(err: Overflow) -> Self {
+        EvaluateError::Overflow(err)
+    }
+}
+
+impl From<InvalidOperator> for EvaluateError {
+    fn from(err: InvalidOperator) -> Self {
+        EvaluateError::InvalidOperator(err)
+    }
+}
+
+/// Apply the operator of a type id to the values of its sub-packets, `None` on overflow
+fn operate<V: Value>(type_id: u8, values: Vec<V>) -> Option<V> {
+    let checked = "Operators are checked before they are evaluated";
+    let mut iter = values.into_iter();
+    let mut compare = |cmp: fn(&V, &V) -> bool| {
+        let (a, b) = iter.next_tuple().expect(checked);
+        V::from_bool(cmp(&a, &b))
+    };
+    match type_id {
+        SUM => iter.try_fold(V::from_bool(false), |a, b| a.checked_add(&b)),
+        PRODUCT => iter.try_fold(V::from_bool(true), |a, b| a.checked_mul(&b)),
+        MINIMUM => Some(iter.min().expect(checked)),
+        MAXIMUM => Some(iter.max().expect(checked)),
+        GREATER_THAN => Some(compare(V::gt)),
+        LESS_THAN => Some(compare(V::lt)),
+        EQUAL_TO => Some(compare(V::eq)),
+        _ => unreachable!("{}", checked),
+    }
+}
+
+impl Packet {
+    /// Evaluate the expression of the packet with checked arithmetic, failing with the first
+    /// packet whose value doesn't fit in `V` or which isn't a valid operator
+    pub fn evaluate<V: Value>(&self) -> Result<V, EvaluateError> {
+        self.fold(&mut |packet, values: Vec<Result<V, EvaluateError>>| {
+            let value = match packet {
+                Packet::Literal { value, .. } => V::from_literal(value),
+                &Packet::Operator {
+                    type_id,
+                    ref children,
+                    ..
+                } => {
+                    check_operator(type_id, children)
+                        .map_err(|reason| InvalidOperator { type_id, reason })?;
+                    operate(type_id, values.into_iter().try_collect()?)
+                }
+            };
+            value.ok_or_else(|| {
+                EvaluateError::Overflow(Overflow {
+                    expression: packet.to_string(),
+                    type_name: std::any::type_name::<V>(),
+                })
+            })
+        })
+    }
+}
+
+pub fn answer1(packet: &Packet) -> u32 {
+    packet.fold(&mut |packet, versions: Vec<u32>| {
+        packet.version() as u32 + versions.into_iter().sum::<u32>()
+    })
+}
+
+pub fn answer2(packet: &Packet) -> BigUint {
+    packet
+        .evaluate()
+        .expect("Decoded operators are valid and big integers can't overflow")
 }
 
-pub fn answer2(mut input: Bits) -> u64 {
-    calculate_operation(&mut input)
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 16;
+    const DATA: &'static str = DATA;
+
+    type Input = Packet;
+    type Answer1 = u32;
+    type Answer2 = BigUint;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(&input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(&input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     const MOCK_DATA_AND_ANSWERS_1: [(&str, u32); 4] = [
         ("8A004A801A8002F478", 16),
@@ -176,24 +847,298 @@ mod tests {
     #[test]
     fn test_answer1_mock_data() {
         for (mock_data, ans) in MOCK_DATA_AND_ANSWERS_1 {
-            assert_eq!(answer1(mock_data.parse().unwrap()), ans);
+            assert_eq!(answer1(&parse(mock_data).unwrap()), ans);
         }
     }
 
     #[test]
     fn test_answer2_mock_data() {
         for (mock_data, ans) in MOCK_DATA_AND_ANSWERS_2 {
-            assert_eq!(answer2(mock_data.parse().unwrap()), ans);
+            assert_eq!(answer2(&parse(mock_data).unwrap()), BigUint::from(ans));
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(
+            parse("D2FE28").unwrap(),
+            Packet::literal(2021u32).with_version(6).unwrap()
+        );
+        let literal = |version, value: u32| Packet::literal(value).with_version(version).unwrap();
+        assert_eq!(
+            parse("38006F45291200").unwrap(),
+            Packet::Operator {
+                version: 1,
+                type_id: 6,
+                length_type: LengthType::Bits,
+                children: vec![literal(6, 10), literal(2, 20)],
+            }
+        );
+        assert_eq!(
+            parse("EE00D40C823060").unwrap(),
+            Packet::Operator {
+                version: 7,
+                type_id: 3,
+                length_type: LengthType::Packets,
+                children: vec![literal(2, 1), literal(4, 2), literal(1, 3)],
+            }
+        );
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(
+            Packet::literal(2021u32).with_version(6).unwrap().to_hex(),
+            "D2FE28"
+        );
+        let packet = Packet::less_than(
+            Packet::literal(10u32).with_version(6).unwrap(),
+            Packet::literal(20u32).with_version(2).unwrap(),
+        );
+        assert_eq!(packet.with_version(1).unwrap().to_hex(), "38006F45291200");
+        let packet = Packet::maximum(vec![
+            Packet::literal(1u32).with_version(2).unwrap(),
+            Packet::literal(2u32).with_version(4).unwrap(),
+            Packet::literal(3u32).with_version(1).unwrap(),
+        ])
+        .unwrap();
+        assert_eq!(
+            packet
+                .with_version(7)
+                .unwrap()
+                .with_length_type(LengthType::Packets)
+                .unwrap()
+                .to_hex(),
+            "EE00D40C823060"
+        );
+        assert_eq!(
+            Packet::literal(1u32).with_version(8),
+            Err(InvalidVersion(8))
+        );
+    }
+
+    #[test]
+    fn test_invalid_operator() {
+        let three_way = Packet::operator(GREATER_THAN, vec![Packet::literal(1u32); 3]);
+        assert_eq!(
+            three_way.unwrap_err().to_string(),
+            "Expected 2 sub-packets for `gt`, found 3"
+        );
+        assert!(Packet::sum(Vec::new()).is_err());
+        assert_eq!(
+            Packet::operator(LITERAL, vec![Packet::literal(1u32)]),
+            Err(InvalidOperator {
+                type_id: LITERAL,
+                reason: "Expected an operator type id, found 4".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_unencodable_operator() {
+        // 86 bits each, too many for the 15 bit length so the sub-packets have to be counted
+        let big = |n| vec![Packet::literal(u64::MAX); n];
+        let packet = Packet::sum(big(2047)).unwrap();
+        assert_eq!(parse(&packet.to_hex()).unwrap(), packet);
+        assert_eq!(
+            Packet::sum(big(2048)).unwrap_err().reason,
+            "Expected fewer than 32768 bits of sub-packets, found 176128"
+        );
+        assert!(Packet::sum(vec![Packet::literal(1u32); 3000]).is_err());
+
+        let packet = Packet::sum(vec![Packet::literal(1u32); 2048]).unwrap();
+        assert_eq!(parse(&packet.to_hex()).unwrap(), packet);
+        assert_eq!(
+            packet
+                .with_length_type(LengthType::Packets)
+                .unwrap_err()
+                .reason,
+            "Expected fewer than 2048 sub-packets, found 2048"
+        );
+        assert_eq!(
+            Packet::sum(big(1000))
+                .unwrap()
+                .with_length_type(LengthType::Bits)
+                .unwrap_err()
+                .reason,
+            "Expected fewer than 32768 bits of sub-packets, found 86000"
+        );
+    }
+
+    #[test]
+    fn test_evaluate() {
+        // 20 groups, more than a u64 can hold
+        let big = Packet::literal(u128::MAX >> 48);
+        let packet = parse(&big.to_hex()).unwrap();
+        assert_eq!(packet, big);
+        assert_eq!(answer2(&packet), BigUint::from(u128::MAX >> 48));
+        assert_eq!(packet.evaluate::<u128>(), Ok(u128::MAX >> 48));
+        assert_eq!(
+            packet.evaluate::<u64>().unwrap_err().to_string(),
+            "1208925819614629174706175 overflows u64"
+        );
+
+        let sum = Packet::sum(vec![Packet::literal(u64::MAX), Packet::literal(1u32)]).unwrap();
+        assert_eq!(answer2(&sum), BigUint::from(u64::MAX) + 1u32);
+        assert_eq!(
+            sum.evaluate::<u64>(),
+            Err(EvaluateError::Overflow(Overflow {
+                expression: format!("(sum {} 1)", u64::MAX),
+                type_name: "u64"
+            }))
+        );
+        let product =
+            Packet::product(vec![Packet::literal(16u32), Packet::literal(16u32)]).unwrap();
+        assert!(product.evaluate::<u8>().is_err());
+        assert_eq!(product.evaluate::<u16>(), Ok(256));
+
+        let one_way = Packet::Operator {
+            version: 0,
+            type_id: GREATER_THAN,
+            length_type: LengthType::Packets,
+            children: vec![Packet::literal(1u32)],
+        };
+        assert_eq!(
+            one_way.evaluate::<u64>().unwrap_err().to_string(),
+            "Expected 2 sub-packets for `gt`, found 1"
+        );
+        let literal_operator = Packet::Operator {
+            version: 0,
+            type_id: LITERAL,
+            length_type: LengthType::Packets,
+            children: vec![Packet::literal(1u32)],
+        };
+        let nested = Packet::sum(vec![literal_operator]).unwrap();
+        assert_eq!(
+            nested.evaluate::<BigUint>(),
+            Err(EvaluateError::InvalidOperator(InvalidOperator {
+                type_id: LITERAL,
+                reason: "Expected an operator type id, found 4".into()
+            }))
+        );
+    }
+
+    /// The column and reason of the error from parsing `input`
+    fn error_at(input: &str) -> (usize, String) {
+        match parse(input) {
+            Err(Error::Parse { column, reason, .. }) => (column, reason),
+            other => panic!("Expected a parse error, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            error_at("D2FE2"),
+            (
+                6,
+                "bit 20: Expected an even number of hex digits, found 5".into()
+            )
+        );
+        assert_eq!(
+            error_at("D2XE28"),
+            (3, "bit 8: Expected a hex digit, found `X`".into())
+        );
+        assert_eq!(
+            error_at("D2FE"),
+            (5, "bit 16: Expected 1 more bits, found 0".into())
+        );
+        assert_eq!(
+            error_at("D2FE29"),
+            (
+                6,
+                "bit 23: Expected only zeros after the packet, found a 1".into()
+            )
+        );
+        let three_way = Packet::Operator {
+            version: 0,
+            type_id: GREATER_THAN,
+            length_type: LengthType::Packets,
+            children: vec![Packet::literal(1u32); 3],
+        };
+        assert_eq!(
+            error_at(&three_way.to_hex()),
+            (1, "bit 0: Expected 2 sub-packets for `gt`, found 3".into())
+        );
+        // The 15 bit length ends in the middle of the second literal
+        let mut bits = Bits::default();
+        bits.push_integral(SUM, 6);
+        bits.push(false);
+        bits.push_integral(15usize, BITS_LEN);
+        Packet::literal(1u32).encode(&mut bits);
+        Packet::literal(2u32).encode(&mut bits);
+        assert_eq!(
+            error_at(&bits.to_string()),
+            (
+                10,
+                "bit 37: Expected the sub-packets to end at bit 37, found them ending at bit 44"
+                    .into()
+            )
+        );
+        // Sums of a single packet nested far deeper than any puzzle input
+        let mut bits = Bits::default();
+        for _ in 0..20_000 {
+            bits.push_integral(SUM, 6);
+            bits.push(true);
+            bits.push_integral(1usize, PACKETS_LEN);
+        }
+        Packet::literal(1u32).encode(&mut bits);
+        let offset = 18 * (MAX_DEPTH + 1);
+        assert_eq!(
+            error_at(&bits.to_string()),
+            (
+                offset / 4 + 1,
+                format!("bit {}: Packets nested deeper than {}", offset, MAX_DEPTH)
+            )
+        );
+        assert!(disassemble(&bits.to_string()).is_err());
+    }
+
+    fn packets() -> impl Strategy<Value = Packet> {
+        let literal = (0..8u8, any::<u128>())
+            .prop_map(|(version, value)| Packet::literal(value).with_version(version).unwrap());
+        literal.prop_recursive(4, 32, 4, |inner| {
+            let length_type = prop_oneof![Just(LengthType::Bits), Just(LengthType::Packets)];
+            let operator = prop_oneof![
+                (0..4u8, prop::collection::vec(inner.clone(), 1..4)),
+                (5..8u8, prop::collection::vec(inner, 2..=2)),
+            ];
+            (0..8u8, length_type, operator).prop_map(
+                |(version, length_type, (type_id, children))| {
+                    Packet::operator(type_id, children)
+                        .unwrap()
+                        .with_version(version)
+                        .unwrap()
+                        .with_length_type(length_type)
+                        .unwrap()
+                },
+            )
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_encode_round_trip(packet in packets()) {
+            prop_assert_eq!(parse(&packet.to_hex()).unwrap(), packet);
         }
     }
 
     #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(load(DATA)), 1007)
+    fn test_display() {
+        let packet = parse("9C0141080250320F1802104A08").unwrap();
+        assert_eq!(packet.to_string(), "(eq (sum 1 3) (product 2 2))");
     }
 
     #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(load(DATA)), 834151779165)
+    fn test_disassemble() {
+        assert_eq!(
+            disassemble("EE00D40C823060").unwrap(),
+            concat!(
+                "     0: v7 max length id 1, 3 packets\n",
+                "    18:   v2 literal = 1\n",
+                "    29:   v4 literal = 2\n",
+                "    40:   v1 literal = 3",
+            )
+        );
     }
 }
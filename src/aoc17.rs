@@ -1,10 +1,13 @@
-use crate::loaders::file_to_string;
+use crate::{
+    loaders::{parse_at, parse_file},
+    Day, Error, Result,
+};
 use itertools::Itertools;
 use std::collections::HashMap;
 
 pub const DATA: &str = "input/aoc17";
 
-#[derive(Debug, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Target {
     x_min: i32,
     x_max: i32,
@@ -12,22 +15,25 @@ pub struct Target {
     y_max: i32,
 }
 
-pub fn load(filename: impl AsRef<std::path::Path>) -> Target {
-    parse_input(&file_to_string(filename))
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<Target> {
+    parse_file(filename, parse)
 }
 
-fn parse_input(input: &str) -> Target {
-    let mut coords = input[13..] // Skip prefix junk
-        .trim_end() // Skip any whitespace at the end
-        .split(", ") // Split into each axis
-        .flat_map(|axis| axis[2..].split("..")) // Split into range parts
-        .map(|coord| coord.parse().unwrap());
-    Target {
-        x_min: coords.next().unwrap(),
-        x_max: coords.next().unwrap(),
-        y_min: coords.next().unwrap(),
-        y_max: coords.next().unwrap(),
-    }
+pub fn parse(input: &str) -> Result<Target> {
+    let area = input.trim_end(); // Skip any whitespace at the end
+    let bad_area = || Error::at(input, area, "Expected `target area: x=A..B, y=C..D`");
+    let (x, y) = area
+        .strip_prefix("target area: x=") // Skip prefix junk
+        .and_then(|axes| axes.split_once(", y=")) // Split into each axis
+        .ok_or_else(bad_area)?;
+    let (x_min, x_max) = x.split_once("..").ok_or_else(bad_area)?; // Split into range parts
+    let (y_min, y_max) = y.split_once("..").ok_or_else(bad_area)?;
+    Ok(Target {
+        x_min: parse_at(input, x_min)?,
+        x_max: parse_at(input, x_max)?,
+        y_min: parse_at(input, y_min)?,
+        y_max: parse_at(input, y_max)?,
+    })
 }
 
 pub fn answer1(input: &Target) -> i32 {
@@ -96,6 +102,7 @@ fn velocity_to_reach_on_time(x: i32, time: f32) -> f32 {
 /// Target can be reached in the targeted time in 2 different ways:
 /// * Right on time without slowing to a stop
 /// * Stopping at the position at any time before (or including) the given time slot
+///
 /// The applicable option is decided by the target time
 fn calc_x_times_and_velocities<'a>(
     target: &Target,
@@ -151,6 +158,29 @@ pub fn answer2(input: &Target) -> usize {
         .sum()
 }
 
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 17;
+    const DATA: &'static str = DATA;
+
+    type Input = Target;
+    type Answer1 = i32;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(&input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(&input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -159,21 +189,11 @@ mod tests {
 
     #[test]
     fn test_answer1_mock_data() {
-        assert_eq!(answer1(&parse_input(MOCK_DATA)), 45)
+        assert_eq!(answer1(&parse(MOCK_DATA).unwrap()), 45)
     }
 
     #[test]
     fn test_answer2_mock_data() {
-        assert_eq!(answer2(&parse_input(MOCK_DATA)), 112)
-    }
-
-    #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(&load(DATA)), 5995)
-    }
-
-    #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(&load(DATA)), 3202)
+        assert_eq!(answer2(&parse(MOCK_DATA).unwrap()), 112)
     }
 }
@@ -1,7 +1,14 @@
 pub const DATA: &str = "input/aoc18";
-pub use crate::loaders::file_to as load;
+use crate::{loaders::parse_file, Day, Error, Result};
 use itertools::Itertools;
-use std::{fmt::Debug, iter::Sum, mem, ops::Add, str::FromStr};
+use std::{
+    cmp::Reverse,
+    fmt::{self, Debug, Display},
+    iter::{self, Sum},
+    mem,
+    ops::Add,
+    str::FromStr,
+};
 use Number::*;
 
 #[derive(Clone, PartialEq)]
@@ -16,10 +23,14 @@ impl Default for Number {
     }
 }
 
+/// The operands are reduced first, since a number nested as deep as the parser allows would
+/// otherwise end up with pairs too deep to explode
 impl Add for Number {
     type Output = Self;
 
-    fn add(self, rhs: Self) -> Self {
+    fn add(mut self, mut rhs: Self) -> Self {
+        self.reduce();
+        rhs.reduce();
         let mut out = Pair(self.into(), rhs.into());
         out.reduce();
         out
@@ -32,63 +43,157 @@ impl Sum for Number {
     }
 }
 
-fn extract_pair(s: &str) -> (&str, &str) {
-    let mut level = 1;
-    let delim = s[1..]
-        .find(|c| match c {
-            '[' => {
-                level += 1;
-                false
-            }
-            ']' => {
-                level -= 1;
-                false
-            }
-            ',' => level == 1, // Outermost pair's delimiter
-            _ => false,
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<impl Iterator<Item = Number>> {
+    parse_file(filename, parse)
+}
+
+/// Parse one number per line, pointing errors at the offending character
+pub fn parse(input: &str) -> Result<impl Iterator<Item = Number>> {
+    let numbers: Vec<Number> = input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.parse().map_err(|err: ParseNumberError| {
+                let at = &line[err.position..];
+                let len = at.chars().next().map_or(0, char::len_utf8);
+                Error::at(input, &at[..len], err)
+            })
         })
-        .unwrap()
-        + 1;
-
-    let end = s[delim + 1..]
-        .find(|c| match c {
-            '[' => {
-                level += 1;
-                false
+        .try_collect()?;
+    if numbers.is_empty() {
+        return Err(Error::at(input, input, "Expected at least one number"));
+    }
+    Ok(numbers.into_iter())
+}
+
+/// An error at a byte position of the parsed string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseNumberError {
+    pub position: usize,
+    pub reason: String,
+}
+
+impl Display for ParseNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "byte {}: {}", self.position, self.reason)
+    }
+}
+
+impl std::error::Error for ParseNumberError {}
+
+/// Pairs may be nested within at most four pairs, any deeper than that and they would have
+/// exploded already
+const MAX_DEPTH: usize = 5;
+
+/// Largest regular number accepted. The puzzle only has single digits. Reducing takes a number of
+/// splits and explodes that grows with the regular numbers, and the magnitude of an unreduced
+/// number is up to 5^5 times its largest regular number, which this keeps within a `u32`.
+const MAX_REGULAR: u32 = 9999;
+
+/// Recursive descent parser of numbers, which may have whitespace between their parts
+struct Parser<'a> {
+    s: &'a str,
+    position: usize,
+    /// Number of pairs the parser is within
+    depth: usize,
+}
+
+impl Parser<'_> {
+    /// Skip any whitespace and look at the next character
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.s[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+        self.s[self.position..].chars().next()
+    }
+
+    fn error(&self, expected: &str) -> ParseNumberError {
+        let found = match self.s[self.position..].chars().next() {
+            Some(c) => format!("`{}`", c),
+            None => "the end".to_owned(),
+        };
+        ParseNumberError {
+            position: self.position,
+            reason: format!("Expected {}, found {}", expected, found),
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseNumberError> {
+        if self.peek() == Some(c) {
+            self.position += c.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(&format!("`{}`", c)))
+        }
+    }
+
+    fn number(&mut self) -> Result<Number, ParseNumberError> {
+        match self.peek() {
+            Some('[') => {
+                if self.depth == MAX_DEPTH {
+                    return Err(ParseNumberError {
+                        position: self.position,
+                        reason: format!("Pairs nested deeper than {}", MAX_DEPTH),
+                    });
+                }
+                self.position += 1;
+                self.depth += 1;
+                let left = self.number()?;
+                self.expect(',')?;
+                let right = self.number()?;
+                self.expect(']')?;
+                self.depth -= 1;
+                Ok(Pair(left.into(), right.into()))
             }
-            ']' => {
-                level -= 1;
-                level == 0
+            Some(c) if c.is_ascii_digit() => {
+                let rest = &self.s[self.position..];
+                let digits = &rest[..rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len())];
+                let n = digits
+                    .parse()
+                    .ok()
+                    .filter(|&n| n <= MAX_REGULAR)
+                    .ok_or_else(|| ParseNumberError {
+                        position: self.position,
+                        reason: format!("Regular number {} is larger than {}", digits, MAX_REGULAR),
+                    })?;
+                self.position += digits.len();
+                Ok(Regular(n))
             }
-            _ => false,
-        })
-        .unwrap()
-        + delim
-        + 1;
-
-    (&s[1..delim], &s[delim + 1..end])
+            _ => Err(self.error("`[` or a digit")),
+        }
+    }
 }
 
 impl FromStr for Number {
-    type Err = &'static str;
+    type Err = ParseNumberError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.chars().next() {
-            Some('[') => {
-                let (left, right) = extract_pair(s);
-                Ok(Pair(
-                    left.parse::<Self>()?.into(),
-                    right.parse::<Self>()?.into(),
-                ))
-            }
-            Some(n) => Ok(Regular(n.to_digit(10).unwrap())), // n is < 10 due to reduction
-            None => panic!("Cannot parse empty string"),
+        let mut parser = Parser {
+            s,
+            position: 0,
+            depth: 0,
+        };
+        let number = parser.number()?;
+        match parser.peek() {
+            None => Ok(number),
+            Some(_) => Err(parser.error("the end of the number")),
+        }
+    }
+}
+
+/// The same syntax as the puzzle input, so that it can be parsed back
+impl Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Regular(n) => write!(f, "{}", n),
+            Pair(left, right) => write!(f, "[{},{}]", left, right),
         }
     }
 }
 
 impl Debug for Number {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Regular(arg0) => write!(f, "{:?}", arg0),
             Self::Pair(arg0, arg1) => write!(f, "[{:?}, {:?}]", arg0, arg1),
@@ -96,6 +201,44 @@ impl Debug for Number {
     }
 }
 
+/// Which element of a pair to go to, a sequence of them from the outermost pair is a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+fn push(mut path: Vec<Side>, side: Side) -> Vec<Side> {
+    path.push(side);
+    path
+}
+
+/// A step of adding and reducing numbers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Add,
+    /// Explode the pair at the path
+    Explode(Vec<Side>),
+    /// Split the regular number at the path
+    Split(Vec<Side>),
+}
+
+/// Formatted as the puzzle's walkthrough, with the path as `L` and `R` for left and right
+impl Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (action, path) = match self {
+            Action::Add => return write!(f, "after addition"),
+            Action::Explode(path) => ("explode", path),
+            Action::Split(path) => ("split", path),
+        };
+        write!(f, "after {} at ", action)?;
+        path.iter().try_for_each(|side| match side {
+            Side::Left => write!(f, "L"),
+            Side::Right => write!(f, "R"),
+        })
+    }
+}
+
 impl Number {
     pub fn unwrap_regular(self) -> u32 {
         match self {
@@ -128,7 +271,14 @@ impl Number {
     }
 
     pub fn explode_pair(&mut self) -> bool {
-        self.explode_pair_inner(0, None, None)
+        self.explode_at().is_some()
+    }
+
+    /// Explode the leftmost pair nested within four pairs, returns the path to it if there was one
+    fn explode_at(&mut self) -> Option<Vec<Side>> {
+        let mut path = self.explode_pair_inner(0, None, None)?;
+        path.reverse(); // Built from the pair up
+        Some(path)
     }
 
     fn explode_pair_inner(
@@ -136,14 +286,20 @@ impl Number {
         level: u32,
         left_of_pair: Option<&mut Self>,
         right_of_pair: Option<&mut Self>,
-    ) -> bool {
+    ) -> Option<Vec<Side>> {
         if matches!(self, Regular(_)) {
-            return false;
+            return None;
         }
         if level < 4 {
             if let Pair(left, right) = self {
-                return left.explode_pair_inner(level + 1, left_of_pair, Some(right))
-                    || right.explode_pair_inner(level + 1, Some(left), right_of_pair);
+                return left
+                    .explode_pair_inner(level + 1, left_of_pair, Some(right))
+                    .map(|path| push(path, Side::Left))
+                    .or_else(|| {
+                        right
+                            .explode_pair_inner(level + 1, Some(left), right_of_pair)
+                            .map(|path| push(path, Side::Right))
+                    });
             }
             unreachable!()
         }
@@ -161,22 +317,61 @@ impl Number {
             *regular_to_the_left += right.unwrap_regular();
         }
 
-        true
+        Some(Vec::new())
     }
 
     pub fn split_num(&mut self) -> bool {
+        self.split_at().is_some()
+    }
+
+    /// Split the leftmost regular number of 10 or more, returns the path to it if there was one
+    fn split_at(&mut self) -> Option<Vec<Side>> {
+        let mut path = self.split_num_inner()?;
+        path.reverse(); // Built from the regular number up
+        Some(path)
+    }
+
+    fn split_num_inner(&mut self) -> Option<Vec<Side>> {
         match self {
-            Pair(a, b) => a.split_num() || b.split_num(),
-            Regular(n) if *n < 10 => false,
+            Pair(a, b) => a
+                .split_num_inner()
+                .map(|path| push(path, Side::Left))
+                .or_else(|| b.split_num_inner().map(|path| push(path, Side::Right))),
+            Regular(n) if *n < 10 => None,
             Regular(n) => {
-                *self = Pair(Regular(*n / 2).into(), Regular((*n + 1) / 2).into()); // (floor, ceil)
-                true
+                *self = Pair(Regular(*n / 2).into(), Regular(n.div_ceil(2)).into()); // (floor, ceil)
+                Some(Vec::new())
             }
         }
     }
 
+    /// Take the first step of reducing the number, if it isn't already reduced
+    pub fn reduce_step(&mut self) -> Option<Action> {
+        self.explode_at()
+            .map(Action::Explode)
+            .or_else(|| self.split_at().map(Action::Split))
+    }
+
     pub fn reduce(&mut self) {
-        while self.explode_pair() || self.split_num() {}
+        while self.reduce_step().is_some() {}
+    }
+
+    /// Every step of reducing the number, with the action taken and the number after it
+    pub fn reduction(mut self) -> impl Iterator<Item = (Action, Number)> {
+        iter::from_fn(move || {
+            let action = self.reduce_step()?;
+            Some((action, self.clone()))
+        })
+    }
+
+    /// Every step of adding two numbers, like the puzzle's walkthrough: first the unreduced pair
+    /// after the addition, and then the number after each explode or split. As with `Add`, the
+    /// operands are reduced before they are added.
+    pub fn add_trace(mut self, mut rhs: Self) -> impl Iterator<Item = (Action, Number)> {
+        self.reduce();
+        rhs.reduce();
+        let sum = Pair(self.into(), rhs.into());
+        iter::once((Action::Add, sum.clone())).chain(sum.reduction())
     }
 
     pub fn magnitude(&self) -> u32 {
@@ -187,16 +382,208 @@ impl Number {
     }
 }
 
-pub fn answer1(numbers: impl Iterator<Item = Number>) -> u32 {
-    numbers.sum::<Number>().magnitude()
+/// The operations of the puzzle, shared by the tree and the flat representation of numbers
+pub trait Snailfish:
+    Clone + Add<Output = Self> + Sum + FromStr<Err = ParseNumberError> + Display + Send + Sync
+{
+    /// Explode and split until neither applies
+    fn reduce(&mut self);
+    fn magnitude(&self) -> u32;
+}
+
+impl Snailfish for Number {
+    fn reduce(&mut self) {
+        Number::reduce(self)
+    }
+
+    fn magnitude(&self) -> u32 {
+        Number::magnitude(self)
+    }
+}
+
+/// Depth at which pairs explode, counted as the number of pairs around a regular number
+const EXPLODE_DEPTH: u8 = 5;
+
+/// A number as its regular numbers from left to right, each with the number of pairs it is
+/// nested within. Adding and reducing only shuffles a vector, instead of walking a tree.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FlatNumber(Vec<(u8, u32)>);
+
+impl From<&Number> for FlatNumber {
+    fn from(number: &Number) -> Self {
+        fn flatten(number: &Number, depth: u8, regulars: &mut Vec<(u8, u32)>) {
+            match number {
+                Regular(n) => regulars.push((depth, *n)),
+                Pair(left, right) => {
+                    flatten(left, depth + 1, regulars);
+                    flatten(right, depth + 1, regulars);
+                }
+            }
+        }
+        let mut regulars = Vec::new();
+        flatten(number, 0, &mut regulars);
+        FlatNumber(regulars)
+    }
+}
+
+impl FlatNumber {
+    /// Combine the regular numbers bottom up, by merging the last two elements of a stack whenever
+    /// they are at the same depth and thus form a pair
+    fn fold<T>(&self, regular: impl Fn(u32) -> T, pair: impl Fn(T, T) -> T) -> T {
+        let mut stack: Vec<(u8, T)> = Vec::new();
+        for &(depth, n) in &self.0 {
+            let (mut depth, mut value) = (depth, regular(n));
+            while let Some((_, left)) = stack.pop_if(|(left_depth, _)| *left_depth == depth) {
+                value = pair(left, value);
+                depth -= 1;
+            }
+            stack.push((depth, value));
+        }
+        let (_, value) = stack
+            .pop()
+            .expect("A number has at least one regular number");
+        value
+    }
+
+    /// Explode the leftmost pair nested within four pairs, returns whether there was one
+    pub fn explode_pair(&mut self) -> bool {
+        let Some(i) = self.0.iter().position(|&(depth, _)| depth >= EXPLODE_DEPTH) else {
+            return false;
+        };
+        // Only pairs of regular numbers can be nested this deep, so the pair is at `i` and `i + 1`
+        let (depth, left) = self.0[i];
+        let (_, right) = self.0.remove(i + 1);
+        if let Some((_, n)) = i.checked_sub(1).map(|j| &mut self.0[j]) {
+            *n += left;
+        }
+        if let Some((_, n)) = self.0.get_mut(i + 1) {
+            *n += right;
+        }
+        self.0[i] = (depth - 1, 0);
+        true
+    }
+
+    /// Split the leftmost regular number of 10 or more, returns whether there was one
+    pub fn split_num(&mut self) -> bool {
+        let Some(i) = self.0.iter().position(|&(_, n)| n >= 10) else {
+            return false;
+        };
+        let (depth, n) = self.0[i];
+        self.0[i] = (depth + 1, n / 2);
+        self.0.insert(i + 1, (depth + 1, n.div_ceil(2)));
+        true
+    }
+}
+
+impl From<&FlatNumber> for Number {
+    fn from(number: &FlatNumber) -> Self {
+        number.fold(Regular, |left, right| Pair(left.into(), right.into()))
+    }
+}
+
+/// The operands are reduced first, the same as for `Number`
+impl Add for FlatNumber {
+    type Output = Self;
+
+    fn add(mut self, mut rhs: Self) -> Self {
+        self.reduce();
+        rhs.reduce();
+        self.0.extend(rhs.0);
+        self.0.iter_mut().for_each(|(depth, _)| *depth += 1);
+        self.reduce();
+        self
+    }
 }
 
-pub fn answer2(numbers: impl Iterator<Item = Number>) -> u32 {
-    numbers
-        .permutations(2) // All ordered pairs
-        .map(|nums| answer1(nums.into_iter()))
-        .max()
-        .unwrap()
+impl Sum for FlatNumber {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.reduce(|a, b| a + b)
+            .unwrap_or_else(|| (&Number::default()).into())
+    }
+}
+
+impl FromStr for FlatNumber {
+    type Err = ParseNumberError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok((&s.parse::<Number>()?).into())
+    }
+}
+
+impl Display for FlatNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Number::from(self))
+    }
+}
+
+impl Snailfish for FlatNumber {
+    fn reduce(&mut self) {
+        while self.explode_pair() || self.split_num() {}
+    }
+
+    fn magnitude(&self) -> u32 {
+        self.fold(|n| n, |left, right| 3 * left + 2 * right)
+    }
+}
+
+pub fn answer1<N: Snailfish>(numbers: impl Iterator<Item = N>) -> u32 {
+    numbers.sum::<N>().magnitude()
+}
+
+/// The ordered pair of numbers with the largest sum, as their indices and the magnitude of the
+/// sum. The pairs are evaluated in parallel with the `rayon` feature.
+pub fn largest_pair<N: Snailfish>(numbers: &[N]) -> Option<((usize, usize), u32)> {
+    let sums = |i: usize| {
+        (0..numbers.len()).filter(move |&j| j != i).map(move |j| {
+            let sum = numbers[i].clone() + numbers[j].clone();
+            ((i, j), sum.magnitude())
+        })
+    };
+    // Pick the first of equally large pairs, whichever order they were evaluated in
+    let key = |&((i, j), magnitude): &((usize, usize), u32)| (magnitude, Reverse((i, j)));
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        (0..numbers.len())
+            .into_par_iter()
+            .flat_map_iter(sums)
+            .max_by_key(key)
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        (0..numbers.len()).flat_map(sums).max_by_key(key)
+    }
+}
+
+pub fn answer2<N: Snailfish>(numbers: impl Iterator<Item = N>) -> Result<u32> {
+    let (_, magnitude) = largest_pair(&numbers.collect_vec())
+        .ok_or_else(|| Error::solve("Expected at least two numbers to add"))?;
+    Ok(magnitude)
+}
+
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 18;
+    const DATA: &'static str = DATA;
+
+    type Input = Vec<FlatNumber>;
+    type Answer1 = u32;
+    type Answer2 = Result<u32>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(parse(input)?
+            .map(|number| FlatNumber::from(&number))
+            .collect())
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(input.into_iter())
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(input.into_iter())
+    }
 }
 
 #[cfg(test)]
@@ -216,28 +603,115 @@ mod tests {
         "[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]\n"
     );
 
-    fn parse_mock_data(data: &str) -> impl Iterator<Item = Number> + '_ {
-        data.lines().map(|line| line.parse().unwrap())
+    fn flat(numbers: impl Iterator<Item = Number>) -> impl Iterator<Item = FlatNumber> {
+        numbers.map(|number| FlatNumber::from(&number))
     }
 
     #[test]
     fn test_answer1_mock_data() {
-        assert_eq!(answer1(parse_mock_data(MOCK_DATA)), 4140)
+        assert_eq!(answer1(parse(MOCK_DATA).unwrap()), 4140);
+        assert_eq!(answer1(flat(parse(MOCK_DATA).unwrap())), 4140)
     }
 
     #[test]
     fn test_answer2_mock_data() {
-        assert_eq!(answer2(parse_mock_data(MOCK_DATA)), 3993)
+        assert_eq!(answer2(parse(MOCK_DATA).unwrap()).unwrap(), 3993);
+        assert_eq!(answer2(flat(parse(MOCK_DATA).unwrap())).unwrap(), 3993)
+    }
+
+    #[test]
+    fn test_single_number() {
+        assert_eq!(answer1(parse("[1,2]\n").unwrap()), 7);
+        assert!(matches!(
+            answer2(parse("[1,2]\n").unwrap()),
+            Err(Error::Solve { .. })
+        ));
+    }
+
+    #[test]
+    fn test_display() {
+        for line in MOCK_DATA.lines() {
+            let number: Number = line.parse().unwrap();
+            assert_eq!(number.to_string(), line);
+            assert_eq!(number.to_string().parse::<Number>().unwrap(), number);
+        }
+        // Intermediate sums from the worked example
+        let mut numbers = parse(MOCK_DATA).unwrap();
+        let mut sum = numbers.next().unwrap();
+        for number in numbers {
+            sum = sum + number;
+            assert_eq!(sum.to_string().parse::<Number>().unwrap(), sum);
+        }
+        assert_eq!(
+            sum.to_string(),
+            "[[[[6,6],[7,6]],[[7,7],[7,0]]],[[[7,7],[7,7]],[[7,8],[9,9]]]]"
+        );
     }
 
     #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(load(DATA)), 3725)
+    fn test_parse() {
+        assert_eq!(
+            " [ 12 , [3,45] ] ".parse(),
+            Ok(Pair(
+                Regular(12).into(),
+                Pair(Regular(3).into(), Regular(45).into()).into()
+            ))
+        );
+        let error = |s: &str| s.parse::<Number>().unwrap_err();
+        assert_eq!(
+            error("[[1,2],3"),
+            ParseNumberError {
+                position: 8,
+                reason: "Expected `]`, found the end".into()
+            }
+        );
+        assert_eq!(error("[1 2]").position, 3);
+        assert_eq!(
+            error("[1,2]]").to_string(),
+            "byte 5: Expected the end of the number, found `]`"
+        );
+        assert_eq!(error("[1,x]").position, 3);
+        assert_eq!(error("").reason, "Expected `[` or a digit, found the end");
+        assert_eq!(
+            error(&"[".repeat(100_000)).to_string(),
+            "byte 5: Pairs nested deeper than 5"
+        );
+        assert_eq!(
+            error("[4000000000,1]").to_string(),
+            "byte 1: Regular number 4000000000 is larger than 9999"
+        );
+        assert_eq!(
+            error("[1,99999999999999999999]").reason,
+            "Regular number 99999999999999999999 is larger than 9999"
+        );
+        let largest = format!("[{0},{0}]", MAX_REGULAR);
+        assert_eq!(
+            answer1(parse(&format!("{0}\n{0}\n", largest)).unwrap()),
+            answer1(flat(parse(&format!("{0}\n{0}\n", largest)).unwrap()))
+        );
+        assert!(matches!(
+            parse("\n").err(),
+            Some(Error::Parse {
+                line: 1,
+                column: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse("[1,2]\n[3,4]x\n").err(),
+            Some(Error::Parse {
+                line: 2,
+                column: 6,
+                ..
+            })
+        ));
     }
 
     #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(load(DATA)), 4832)
+    fn test_largest_pair() {
+        let numbers = parse(MOCK_DATA).unwrap().collect_vec();
+        assert_eq!(largest_pair(&numbers), Some(((8, 0), 3993)));
+        assert_eq!(largest_pair(&numbers[..1]), None);
     }
 
     #[test]
@@ -260,10 +734,32 @@ mod tests {
         ];
 
         for [data, ans] in EXAMPLES {
-            let mut data = data.parse::<Number>().unwrap();
-            assert!(data.explode_pair()); // An explosion should give a "true"
-            assert_eq!(data, ans.parse().unwrap());
+            let mut number = data.parse::<Number>().unwrap();
+            assert!(number.explode_pair()); // An explosion should give a "true"
+            assert_eq!(number, ans.parse().unwrap());
+            let mut number = data.parse::<FlatNumber>().unwrap();
+            assert!(number.explode_pair());
+            assert_eq!(number.to_string(), ans);
+        }
+        // Pairs nested within more than four pairs are never formed by reduction, and the two
+        // representations would explode them differently
+        let too_deep = "[[[[[[1,2],3],4],5],6],7]";
+        assert_eq!(too_deep.parse::<Number>().unwrap_err().position, 5);
+        assert_eq!(too_deep.parse::<FlatNumber>().unwrap_err().position, 5);
+    }
+
+    #[test]
+    fn test_add_unreduced() {
+        fn add<N: Snailfish>(a: &str, b: &str) -> String {
+            let mut sum = a.parse::<N>().unwrap() + b.parse::<N>().unwrap();
+            sum.reduce();
+            sum.to_string()
         }
+        // The first operand is reduced to [[[[0,5],[5,5]],3],4] before the addition
+        let (a, b) = ("[[[[1,[9,8]],2],3],4]", "[1,1]");
+        assert_eq!(add::<Number>(a, b), "[[[[0,5],8],4],[1,1]]");
+        assert_eq!(add::<FlatNumber>(a, b), add::<Number>(a, b));
+        assert_eq!(add::<FlatNumber>(b, a), add::<Number>(b, a));
     }
 
     #[test]
@@ -303,10 +799,35 @@ mod tests {
         ];
 
         for [data, ans] in EXAMPLES {
-            assert_eq!(parse_mock_data(data).sum::<Number>(), ans.parse().unwrap());
+            assert_eq!(parse(data).unwrap().sum::<Number>(), ans.parse().unwrap());
+            assert_eq!(
+                flat(parse(data).unwrap()).sum::<FlatNumber>(),
+                ans.parse().unwrap()
+            );
         }
     }
 
+    #[test]
+    fn test_add_trace() {
+        let a: Number = "[[[[4,3],4],4],[7,[[8,4],9]]]".parse().unwrap();
+        let b: Number = "[1,1]".parse().unwrap();
+        let trace = a
+            .add_trace(b)
+            .map(|(action, number)| format!("{}: {}", action, number))
+            .collect_vec();
+        assert_eq!(
+            trace,
+            [
+                "after addition: [[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]",
+                "after explode at LLLL: [[[[0,7],4],[7,[[8,4],9]]],[1,1]]",
+                "after explode at LRRL: [[[[0,7],4],[15,[0,13]]],[1,1]]",
+                "after split at LRL: [[[[0,7],4],[[7,8],[0,13]]],[1,1]]",
+                "after split at LRRR: [[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]",
+                "after explode at LRRR: [[[[0,7],4],[[7,8],[6,0]]],[8,1]]",
+            ]
+        );
+    }
+
     #[test]
     fn test_magnitude() {
         const EXAMPLES: [(&str, u32); 6] = [
@@ -323,6 +844,7 @@ mod tests {
 
         for (data, ans) in EXAMPLES {
             assert_eq!(data.parse::<Number>().unwrap().magnitude(), ans);
+            assert_eq!(data.parse::<FlatNumber>().unwrap().magnitude(), ans);
         }
     }
 }
@@ -0,0 +1,402 @@
+use crate::{
+    loaders::{parse_at, parse_file},
+    Day, Error, Result,
+};
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+
+pub const DATA: &str = "input/aoc19";
+
+pub type Point = [i32; 3];
+pub type Report = Vec<Point>; // Beacons relative to a scanner, in the scanner's own orientation
+
+/// Scanners overlap if they detect at least this many of the same beacons
+const OVERLAP: usize = 12;
+/// Distances between every pair of the overlapping beacons
+const OVERLAP_PAIRS: usize = OVERLAP * (OVERLAP - 1) / 2;
+
+/// The beacons and the positions of the scanners, relative to the first scanner
+#[derive(Debug, Clone)]
+pub struct Map {
+    beacons: HashSet<Point>,
+    scanners: Vec<Point>,
+}
+
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<Vec<Report>> {
+    parse_file(filename, parse)
+}
+
+/// Read the report of each scanner
+pub fn parse(input: &str) -> Result<Vec<Report>> {
+    let mut reports = Vec::new();
+    for line in input.lines().filter(|line| !line.trim().is_empty()) {
+        if line.starts_with("---") {
+            reports.push(Vec::new());
+            continue;
+        }
+        let report: &mut Report = reports
+            .last_mut()
+            .ok_or_else(|| Error::at(input, line, "Expected a `--- scanner N ---` header"))?;
+        let coordinates: Vec<i32> = line
+            .split(',')
+            .map(|coordinate| parse_at(input, coordinate.trim()))
+            .try_collect()?;
+        report.push(
+            coordinates
+                .try_into()
+                .map_err(|_| Error::at(input, line, "Expected a point like `x,y,z`"))?,
+        );
+    }
+    if reports.is_empty() {
+        return Err(Error::at(input, input, "Expected at least one scanner"));
+    }
+    Ok(reports)
+}
+
+/// All 24 rotations, as the axis each coordinate is taken from and whether it is negated
+fn rotations() -> impl Iterator<Item = ([usize; 3], [i32; 3])> {
+    [
+        [0, 1, 2],
+        [1, 2, 0],
+        [2, 0, 1],
+        [0, 2, 1],
+        [2, 1, 0],
+        [1, 0, 2],
+    ]
+    .into_iter()
+    .enumerate()
+    .flat_map(|(i, axes)| {
+        let parity = if i < 3 { 1 } else { -1 }; // The last three swap two axes
+        (0..8)
+            .map(move |signs: i32| {
+                let signs = [0, 1, 2].map(|bit| 1 - 2 * (signs >> bit & 1));
+                (axes, signs)
+            })
+            .filter(move |(_, signs)| signs.iter().product::<i32>() == parity)
+    })
+}
+
+fn rotate(point: &Point, (axes, signs): &([usize; 3], [i32; 3])) -> Point {
+    [0, 1, 2].map(|i| signs[i] * point[axes[i]])
+}
+
+fn sub(a: &Point, b: &Point) -> Point {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn add(a: &Point, b: &Point) -> Point {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn manhattan(a: &Point, b: &Point) -> i32 {
+    sub(a, b).iter().map(|d| d.abs()).sum()
+}
+
+/// Squared distances between every pair of beacons, which don't depend on the orientation. The
+/// same distance may be found between several pairs.
+fn fingerprint(report: &Report) -> HashMap<i64, usize> {
+    report
+        .iter()
+        .tuple_combinations()
+        .map(|(a, b)| sub(a, b).iter().map(|&d| (d as i64).pow(2)).sum())
+        .counts()
+}
+
+/// Number of pairs of beacons of two fingerprints which may be the same pairs
+fn shared_pairs(a: &HashMap<i64, usize>, b: &HashMap<i64, usize>) -> usize {
+    a.iter()
+        .filter_map(|(distance, &count)| Some(count.min(*b.get(distance)?)))
+        .sum()
+}
+
+/// Find the rotation and position of the scanner of `report` such that at least `OVERLAP` of its
+/// beacons line up with the already aligned `known` beacons. Returns the aligned beacons and the
+/// position of the scanner.
+fn align(known: &Report, report: &Report) -> Option<(Report, Point)> {
+    rotations().find_map(|rotation| {
+        let rotated = report
+            .iter()
+            .map(|beacon| rotate(beacon, &rotation))
+            .collect_vec();
+        let mut offsets = HashMap::new();
+        let position = known
+            .iter()
+            .cartesian_product(&rotated)
+            .map(|(known, beacon)| sub(known, beacon))
+            .find(|&offset| {
+                let count = offsets.entry(offset).or_insert(0);
+                *count += 1;
+                *count >= OVERLAP
+            })?;
+        let aligned = rotated
+            .iter()
+            .map(|beacon| add(beacon, &position))
+            .collect();
+        Some((aligned, position))
+    })
+}
+
+/// Align every scanner to the first one, failing if a scanner doesn't overlap with the others
+pub fn locate(reports: &[Report]) -> Result<Map> {
+    let fingerprints = reports.iter().map(fingerprint).collect_vec();
+    let mut aligned = vec![None; reports.len()];
+    let mut positions = vec![[0; 3]; reports.len()];
+    aligned[0] = Some(reports[0].clone());
+    let mut queue = vec![0];
+    while let Some(i) = queue.pop() {
+        for j in 0..reports.len() {
+            if aligned[j].is_some()
+                || shared_pairs(&fingerprints[i], &fingerprints[j]) < OVERLAP_PAIRS
+            {
+                continue;
+            }
+            if let Some((beacons, position)) = align(aligned[i].as_ref().unwrap(), &reports[j]) {
+                aligned[j] = Some(beacons);
+                positions[j] = position;
+                queue.push(j);
+            }
+        }
+    }
+    let mut beacons = HashSet::new();
+    for (i, report) in aligned.into_iter().enumerate() {
+        beacons.extend(report.ok_or_else(|| {
+            Error::solve(format!(
+                "Scanner {} doesn't detect at least {} of the beacons of another scanner",
+                i, OVERLAP
+            ))
+        })?);
+    }
+    Ok(Map {
+        beacons,
+        scanners: positions,
+    })
+}
+
+pub fn answer1(reports: &[Report]) -> Result<usize> {
+    Ok(locate(reports)?.beacons.len())
+}
+
+pub fn answer2(reports: &[Report]) -> Result<i32> {
+    Ok(locate(reports)?
+        .scanners
+        .iter()
+        .tuple_combinations()
+        .map(|(a, b)| manhattan(a, b))
+        .max()
+        .unwrap_or(0))
+}
+
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 19;
+    const DATA: &'static str = DATA;
+
+    type Input = Vec<Report>;
+    type Answer1 = Result<usize>;
+    type Answer2 = Result<i32>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(&input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(&input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOCK_DATA: &str = concat!(
+        "--- scanner 0 ---\n",
+        "404,-588,-901\n",
+        "528,-643,409\n",
+        "-838,591,734\n",
+        "390,-675,-793\n",
+        "-537,-823,-458\n",
+        "-485,-357,347\n",
+        "-345,-311,381\n",
+        "-661,-816,-575\n",
+        "-876,649,763\n",
+        "-618,-824,-621\n",
+        "553,345,-567\n",
+        "474,580,667\n",
+        "-447,-329,318\n",
+        "-584,868,-557\n",
+        "544,-627,-890\n",
+        "564,392,-477\n",
+        "455,729,728\n",
+        "-892,524,684\n",
+        "-689,845,-530\n",
+        "423,-701,434\n",
+        "7,-33,-71\n",
+        "630,319,-379\n",
+        "443,580,662\n",
+        "-789,900,-551\n",
+        "459,-707,401\n",
+        "\n",
+        "--- scanner 1 ---\n",
+        "686,422,578\n",
+        "605,423,415\n",
+        "515,917,-361\n",
+        "-336,658,858\n",
+        "95,138,22\n",
+        "-476,619,847\n",
+        "-340,-569,-846\n",
+        "567,-361,727\n",
+        "-460,603,-452\n",
+        "669,-402,600\n",
+        "729,430,532\n",
+        "-500,-761,534\n",
+        "-322,571,750\n",
+        "-466,-666,-811\n",
+        "-429,-592,574\n",
+        "-355,545,-477\n",
+        "703,-491,-529\n",
+        "-328,-685,520\n",
+        "413,935,-424\n",
+        "-391,539,-444\n",
+        "586,-435,557\n",
+        "-364,-763,-893\n",
+        "807,-499,-711\n",
+        "755,-354,-619\n",
+        "553,889,-390\n",
+        "\n",
+        "--- scanner 2 ---\n",
+        "649,640,665\n",
+        "682,-795,504\n",
+        "-784,533,-524\n",
+        "-644,584,-595\n",
+        "-588,-843,648\n",
+        "-30,6,44\n",
+        "-674,560,763\n",
+        "500,723,-460\n",
+        "609,671,-379\n",
+        "-555,-800,653\n",
+        "-675,-892,-343\n",
+        "697,-426,-610\n",
+        "578,704,681\n",
+        "493,664,-388\n",
+        "-671,-858,530\n",
+        "-667,343,800\n",
+        "571,-461,-707\n",
+        "-138,-166,112\n",
+        "-889,563,-600\n",
+        "646,-828,498\n",
+        "640,759,510\n",
+        "-630,509,768\n",
+        "-681,-892,-333\n",
+        "673,-379,-804\n",
+        "-742,-814,-386\n",
+        "577,-820,562\n",
+        "\n",
+        "--- scanner 3 ---\n",
+        "-589,542,597\n",
+        "605,-692,669\n",
+        "-500,565,-823\n",
+        "-660,373,557\n",
+        "-458,-679,-417\n",
+        "-488,449,543\n",
+        "-626,468,-788\n",
+        "338,-750,-386\n",
+        "528,-832,-391\n",
+        "562,-778,733\n",
+        "-938,-730,414\n",
+        "543,643,-506\n",
+        "-524,371,-870\n",
+        "407,773,750\n",
+        "-104,29,83\n",
+        "378,-903,-323\n",
+        "-778,-728,485\n",
+        "426,699,580\n",
+        "-438,-605,-362\n",
+        "-469,-447,-387\n",
+        "509,732,623\n",
+        "647,635,-688\n",
+        "-868,-804,481\n",
+        "614,-800,639\n",
+        "595,780,-596\n",
+        "\n",
+        "--- scanner 4 ---\n",
+        "727,592,562\n",
+        "-293,-554,779\n",
+        "441,611,-461\n",
+        "-714,465,-776\n",
+        "-743,427,-804\n",
+        "-660,-479,-426\n",
+        "832,-632,460\n",
+        "927,-485,-438\n",
+        "408,393,-506\n",
+        "466,436,-512\n",
+        "110,16,151\n",
+        "-258,-428,682\n",
+        "-393,719,612\n",
+        "-211,-452,876\n",
+        "808,-476,-593\n",
+        "-575,615,604\n",
+        "-485,667,467\n",
+        "-680,325,-822\n",
+        "-627,-443,-432\n",
+        "872,-547,-609\n",
+        "833,512,582\n",
+        "807,604,487\n",
+        "839,-516,451\n",
+        "891,-625,532\n",
+        "-652,-548,-490\n",
+        "30,-46,-14\n",
+    );
+
+    #[test]
+    fn test_rotations() {
+        let rotated: HashSet<_> = rotations().map(|r| rotate(&[1, 2, 3], &r)).collect();
+        assert_eq!(rotated.len(), 24);
+    }
+
+    #[test]
+    fn test_answer1_mock_data() {
+        assert_eq!(answer1(&parse(MOCK_DATA).unwrap()).unwrap(), 79)
+    }
+
+    #[test]
+    fn test_answer2_mock_data() {
+        assert_eq!(answer2(&parse(MOCK_DATA).unwrap()).unwrap(), 3621)
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        // Each of the distances between neighbours is found twice
+        let report = vec![[0, 0, 0], [1, 0, 0], [2, 0, 0]];
+        let fingerprint = fingerprint(&report);
+        assert_eq!(fingerprint, HashMap::from([(1, 2), (4, 1)]));
+        assert_eq!(shared_pairs(&fingerprint, &fingerprint), 3);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert!(matches!(
+            parse(&MOCK_DATA.replace("-345,-311,381", "-345,-311")).err(),
+            Some(Error::Parse {
+                line: 8,
+                column: 1,
+                ..
+            })
+        ));
+        assert!(parse("1,2,3\n").is_err());
+    }
+
+    #[test]
+    fn test_unaligned_scanner() {
+        let input = format!("{}\n--- scanner 5 ---\n1,2,3\n", MOCK_DATA);
+        let reports = parse(&input).unwrap();
+        assert_eq!(
+            answer1(&reports).unwrap_err().to_string(),
+            "Scanner 5 doesn't detect at least 12 of the beacons of another scanner"
+        );
+    }
+}
@@ -1,6 +1,44 @@
-pub use crate::loaders::file_to_lines as load;
+use crate::{
+    loaders::{parse_at, parse_file},
+    Day, Error, Result,
+};
 pub const DATA: &str = "input/aoc2";
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Forward(u32),
+    Down(u32),
+    Up(u32),
+}
+
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<impl Iterator<Item = Command>> {
+    parse_file(filename, parse)
+}
+
+/// Parse one command per line, like `forward 5`
+pub fn parse(input: &str) -> Result<impl Iterator<Item = Command>> {
+    let commands: Vec<Command> = input
+        .lines()
+        .map(|line| {
+            let (direction, value) = line
+                .split_once(' ')
+                .ok_or_else(|| Error::at(input, line, "Expected a command like `forward 5`"))?;
+            let value = parse_at(input, value)?;
+            match direction {
+                "forward" => Ok(Command::Forward(value)),
+                "down" => Ok(Command::Down(value)),
+                "up" => Ok(Command::Up(value)),
+                _ => Err(Error::at(
+                    input,
+                    direction,
+                    "Expected `forward`, `down` or `up`",
+                )),
+            }
+        })
+        .collect::<Result<_>>()?;
+    Ok(commands.into_iter())
+}
+
 #[derive(Default)]
 struct Position {
     depth: u32,
@@ -8,57 +46,98 @@ struct Position {
     aim: u32,
 }
 
-fn parse_direction(input: String) -> (String, u32) {
-    let (direction, value) = input.split_once(' ').expect("No space");
-    let value = value.parse().expect("Int Parse Failed");
-    (direction.to_string(), value)
+/// Error for a position the submarine can't reach, like flying above the surface
+fn out_of_range() -> Error {
+    Error::solve("The submarine leaves the range of depths and distances")
 }
 
 impl Position {
-    fn add_direction(&mut self, input: String) {
-        let (direction, value) = parse_direction(input);
-        match direction.as_ref() {
-            "forward" => self.horizontal += value,
-            "down" => self.depth += value,
-            "up" => self.depth -= value,
-            _ => panic!("{}", direction),
+    fn add_direction(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Forward(value) => {
+                self.horizontal = self
+                    .horizontal
+                    .checked_add(value)
+                    .ok_or_else(out_of_range)?
+            }
+            Command::Down(value) => {
+                self.depth = self.depth.checked_add(value).ok_or_else(out_of_range)?
+            }
+            Command::Up(value) => {
+                self.depth = self.depth.checked_sub(value).ok_or_else(out_of_range)?
+            }
         }
+        Ok(())
     }
-    fn add_direction_with_aim(&mut self, input: String) {
-        let (direction, value) = parse_direction(input);
-        match direction.as_ref() {
-            "forward" => {
-                self.horizontal += value;
-                self.depth += self.aim * value;
+    fn add_direction_with_aim(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Forward(value) => {
+                self.horizontal = self
+                    .horizontal
+                    .checked_add(value)
+                    .ok_or_else(out_of_range)?;
+                self.depth = self
+                    .aim
+                    .checked_mul(value)
+                    .and_then(|dive| self.depth.checked_add(dive))
+                    .ok_or_else(out_of_range)?;
+            }
+            Command::Down(value) => {
+                self.aim = self.aim.checked_add(value).ok_or_else(out_of_range)?
+            }
+            Command::Up(value) => {
+                self.aim = self.aim.checked_sub(value).ok_or_else(out_of_range)?
             }
-            "down" => self.aim += value,
-            "up" => self.aim -= value,
-            _ => panic!("{}", direction),
         }
+        Ok(())
     }
 }
 
-fn solve<F>(input: impl Iterator<Item = String>, method: F) -> u32
+fn solve<F>(mut input: impl Iterator<Item = Command>, method: F) -> Result<u32>
 where
-    F: Fn(&mut Position, String),
+    F: Fn(&mut Position, Command) -> Result<()>,
 {
     let mut pos = Position::default();
-    input.for_each(|dir| method(&mut pos, dir));
-    pos.horizontal * pos.depth
+    input.try_for_each(|dir| method(&mut pos, dir))?;
+    pos.horizontal
+        .checked_mul(pos.depth)
+        .ok_or_else(out_of_range)
 }
 
-pub fn answer1(input: impl Iterator<Item = String>) -> u32 {
+pub fn answer1(input: impl Iterator<Item = Command>) -> Result<u32> {
     solve(input, Position::add_direction)
 }
 
-pub fn answer2(input: impl Iterator<Item = String>) -> u32 {
+pub fn answer2(input: impl Iterator<Item = Command>) -> Result<u32> {
     solve(input, Position::add_direction_with_aim)
 }
 
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 2;
+    const DATA: &'static str = DATA;
+
+    type Input = Vec<Command>;
+    type Answer1 = Result<u32>;
+    type Answer2 = Result<u32>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(parse(input)?.collect())
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(input.into_iter())
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(input.into_iter())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mockers::*;
 
     const MOCK_DATA: &str = concat!(
         "forward 5\n",
@@ -71,21 +150,45 @@ mod tests {
 
     #[test]
     fn test_answer1_mock_data() {
-        assert_eq!(answer1(str_lines_to_string(MOCK_DATA)), 150)
+        assert_eq!(answer1(parse(MOCK_DATA).unwrap()).unwrap(), 150)
     }
 
     #[test]
     fn test_answer2_mock_data() {
-        assert_eq!(answer2(str_lines_to_string(MOCK_DATA)), 900)
+        assert_eq!(answer2(parse(MOCK_DATA).unwrap()).unwrap(), 900)
     }
 
     #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(load(DATA)), 1524750)
+    fn test_above_surface() {
+        let input = "up 3\nforward 1\n";
+        assert!(matches!(
+            answer1(parse(input).unwrap()),
+            Err(Error::Solve { .. })
+        ));
+        assert!(matches!(
+            answer2(parse(input).unwrap()),
+            Err(Error::Solve { .. })
+        ));
     }
 
     #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(load(DATA)), 1592426537)
+    fn test_parse_error() {
+        assert!(matches!(
+            parse("forward 5\nsideways 2\n").err(),
+            Some(Error::Parse {
+                line: 2,
+                column: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse("forward 5\ndown x\n").err(),
+            Some(Error::Parse {
+                line: 2,
+                column: 6,
+                ..
+            })
+        ));
+        assert!(parse("forward\n").is_err());
     }
 }
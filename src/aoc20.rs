@@ -0,0 +1,202 @@
+use crate::{grid::Grid, loaders::parse_file, Day, Error, Result};
+
+pub const DATA: &str = "input/aoc20";
+
+const ALGORITHM_LENGTH: usize = 512;
+
+type Algorithm = Vec<bool>;
+/// The lit pixels of the interesting part of the image, and whether the infinite rest is lit
+#[derive(Debug, Clone)]
+pub struct Image {
+    pixels: Grid<bool>,
+    background: bool,
+}
+
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<(Algorithm, Image)> {
+    parse_file(filename, parse)
+}
+
+fn parse_pixel(c: char) -> Result<bool, &'static str> {
+    match c {
+        '#' => Ok(true),
+        '.' => Ok(false),
+        _ => Err("Expected `#` or `.`"),
+    }
+}
+
+pub fn parse(input: &str) -> Result<(Algorithm, Image)> {
+    let (algorithm, image) = input
+        .split_once("\n\n")
+        .ok_or_else(|| Error::at(input, input, "Expected an algorithm and an image"))?;
+    let algorithm = algorithm
+        .char_indices()
+        .map(|(i, c)| {
+            parse_pixel(c).map_err(|err| Error::at(input, &algorithm[i..i + c.len_utf8()], err))
+        })
+        .collect::<Result<Algorithm>>()?;
+    if algorithm.len() != ALGORITHM_LENGTH {
+        let reason = format!("Expected {} pixels in the algorithm", ALGORITHM_LENGTH);
+        return Err(Error::at(input, &input[..algorithm.len()], reason));
+    }
+    // A lit background would stay lit after an even number of steps
+    if algorithm[0] && algorithm[ALGORITHM_LENGTH - 1] {
+        let last = &input[ALGORITHM_LENGTH - 1..ALGORITHM_LENGTH];
+        let reason = "Expected `.` when the algorithm starts with `#`, or infinitely many pixels \
+                      stay lit";
+        return Err(Error::at(input, last, reason));
+    }
+    let pixels = Grid::parse_with(image, parse_pixel).map_err(|err| err.within(input, image))?;
+    Ok((
+        algorithm,
+        Image {
+            pixels,
+            background: false,
+        },
+    ))
+}
+
+/// Enhance the image once, growing it by one pixel on every side
+fn enhance(algorithm: &Algorithm, image: &Image) -> Image {
+    let old = &image.pixels;
+    let (height, width) = (old.height() + 2, old.width() + 2);
+    let pixel = |row: isize, col: isize| -> bool {
+        // Coordinates in the old image, which lies one pixel in from the new one
+        match (usize::try_from(row - 1), usize::try_from(col - 1)) {
+            (Ok(row), Ok(col)) => old.get((row, col)).copied().unwrap_or(image.background),
+            _ => image.background,
+        }
+    };
+    let pixels = (0..height as isize)
+        .flat_map(|row| (0..width as isize).map(move |col| (row, col)))
+        .map(|(row, col)| {
+            let index = (-1..=1)
+                .flat_map(|d_row| (-1..=1).map(move |d_col| (d_row, d_col)))
+                .fold(0, |index, (d_row, d_col)| {
+                    index << 1 | pixel(row + d_row, col + d_col) as usize
+                });
+            algorithm[index]
+        })
+        .collect();
+    // The infinite background is either all dark or all lit
+    let background = if image.background {
+        ALGORITHM_LENGTH - 1
+    } else {
+        0
+    };
+    Image {
+        pixels: Grid::from_vec(pixels, width),
+        background: algorithm[background],
+    }
+}
+
+/// Count the lit pixels after an even number of steps, which parsing ensures leaves the
+/// background dark
+fn count_lit(algorithm: &Algorithm, mut image: Image, steps: usize) -> usize {
+    for _ in 0..steps {
+        image = enhance(algorithm, &image);
+    }
+    debug_assert!(!image.background, "Infinitely many pixels are lit");
+    image.pixels.iter().filter(|&&lit| lit).count()
+}
+
+pub fn answer1((algorithm, image): (Algorithm, Image)) -> usize {
+    count_lit(&algorithm, image, 2)
+}
+
+pub fn answer2((algorithm, image): (Algorithm, Image)) -> usize {
+    count_lit(&algorithm, image, 50)
+}
+
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 20;
+    const DATA: &'static str = DATA;
+
+    type Input = (Algorithm, Image);
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOCK_DATA: &str = concat!(
+        "..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..###",
+        "###.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.",
+        "####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.",
+        "######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.",
+        "#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.",
+        "####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###",
+        "..#####........#..####......#..#",
+        "\n",
+        "\n",
+        "#..#.\n",
+        "#....\n",
+        "##..#\n",
+        "..#..\n",
+        "..###\n",
+    );
+
+    #[test]
+    fn test_answer1_mock_data() {
+        assert_eq!(answer1(parse(MOCK_DATA).unwrap()), 35);
+    }
+
+    #[test]
+    fn test_answer2_mock_data() {
+        assert_eq!(answer2(parse(MOCK_DATA).unwrap()), 3351);
+    }
+
+    #[test]
+    fn test_flickering_background() {
+        // Light the pixels lit before, and every pixel of an unlit neighbourhood, so the infinite
+        // background flips to lit and back again
+        let algorithm: String = (0..ALGORITHM_LENGTH)
+            .map(|index| index == 0 || (index != ALGORITHM_LENGTH - 1 && index & 1 << 4 != 0))
+            .map(|lit| if lit { '#' } else { '.' })
+            .collect();
+        let input = format!("{}\n\n#", algorithm);
+        assert_eq!(answer1(parse(&input).unwrap()), 17);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = parse(&MOCK_DATA.replace("..#..\n", "..x..\n")).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::Parse {
+                    line: 6,
+                    column: 3,
+                    ..
+                }
+            ),
+            "{}",
+            err
+        );
+        assert!(parse("#.#\n\n#").is_err());
+        let always_lit = format!("{}\n\n#", "#".repeat(ALGORITHM_LENGTH));
+        assert!(matches!(
+            parse(&always_lit),
+            Err(Error::Parse {
+                line: 1,
+                column: 512,
+                ..
+            })
+        ));
+    }
+}
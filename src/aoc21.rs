@@ -0,0 +1,136 @@
+use crate::{
+    loaders::{parse_at, parse_file},
+    Day, Error, Result,
+};
+use itertools::Itertools;
+use std::collections::HashMap;
+
+pub const DATA: &str = "input/aoc21";
+
+type Positions = [u8; 2];
+
+/// Number of ways to roll each sum with three throws of a three-sided Dirac die
+const DIRAC_ROLLS: [(u8, u64); 7] = [(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)];
+
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<Positions> {
+    parse_file(filename, parse)
+}
+
+pub fn parse(input: &str) -> Result<Positions> {
+    let positions: Vec<u8> = input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (_, position) = line.split_once("starting position: ").ok_or_else(|| {
+                Error::at(input, line, "Expected `Player N starting position: P`")
+            })?;
+            let position = position.trim();
+            match parse_at(input, position)? {
+                position @ 1..=10 => Ok(position),
+                _ => Err(Error::at(input, position, "Position must be within 1..=10")),
+            }
+        })
+        .try_collect()?;
+    positions
+        .try_into()
+        .map_err(|_| Error::at(input, input, "Expected exactly two players"))
+}
+
+/// Move `steps` forward from `position` on the circular track of 1..=10
+fn advance(position: u8, steps: u32) -> u8 {
+    ((position as u32 - 1 + steps) % 10 + 1) as u8
+}
+
+pub fn answer1(mut positions: Positions) -> u32 {
+    let mut scores = [0u32; 2];
+    let mut die = (1..=100).cycle();
+    let mut rolls = 0;
+    for player in [0, 1].into_iter().cycle() {
+        let steps: u32 = die.by_ref().take(3).sum();
+        rolls += 3;
+        positions[player] = advance(positions[player], steps);
+        scores[player] += positions[player] as u32;
+        if scores[player] >= 1000 {
+            return scores[1 - player] * rolls;
+        }
+    }
+    unreachable!()
+}
+
+/// Number of universes in which the current and the other player win respectively, given the
+/// positions and scores with the current player first
+fn count_wins(
+    positions: Positions,
+    scores: [u8; 2],
+    memo: &mut HashMap<(Positions, [u8; 2]), [u64; 2]>,
+) -> [u64; 2] {
+    if let Some(&wins) = memo.get(&(positions, scores)) {
+        return wins;
+    }
+    let mut wins = [0; 2];
+    for (steps, universes) in DIRAC_ROLLS {
+        let position = advance(positions[0], steps as u32);
+        let score = scores[0] + position;
+        if score >= 21 {
+            wins[0] += universes;
+        } else {
+            // The other player goes next, so swap places
+            let [other, current] = count_wins([positions[1], position], [scores[1], score], memo);
+            wins[0] += universes * current;
+            wins[1] += universes * other;
+        }
+    }
+    memo.insert((positions, scores), wins);
+    wins
+}
+
+pub fn answer2(positions: Positions) -> u64 {
+    let wins = count_wins(positions, [0, 0], &mut HashMap::new());
+    wins[0].max(wins[1])
+}
+
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 21;
+    const DATA: &'static str = DATA;
+
+    type Input = Positions;
+    type Answer1 = u32;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOCK_DATA: &str = "Player 1 starting position: 4\nPlayer 2 starting position: 8\n";
+
+    #[test]
+    fn test_answer1_mock_data() {
+        assert_eq!(answer1(parse(MOCK_DATA).unwrap()), 739785)
+    }
+
+    #[test]
+    fn test_answer2_mock_data() {
+        assert_eq!(answer2(parse(MOCK_DATA).unwrap()), 444356092776315)
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("Player 1 starting position: 4\n").is_err());
+        assert!(parse("Player 1 starting position: 4\nPlayer 2 starting position: 11\n").is_err());
+    }
+}
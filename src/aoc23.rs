@@ -0,0 +1,260 @@
+use crate::{
+    loaders::parse_file,
+    search::{self, Graph},
+    Day, Error, Result,
+};
+
+pub const DATA: &str = "input/aoc23";
+
+const HALLWAY: usize = 11;
+const ROOMS: usize = 4;
+const MAX_DEPTH: usize = 4;
+const EMPTY: u8 = b'.';
+/// Amphipods may not stop right outside of a room
+const HALLWAY_STOPS: [usize; 7] = [0, 1, 3, 5, 7, 9, 10];
+/// Rows inserted between the first and the last row of the rooms once the diagram is unfolded
+const UNFOLDED: [[u8; ROOMS]; 2] = [*b"DCBA", *b"DBAC"];
+/// Rows of the diagram as given, leaving room for unfolding it
+const FOLDED_DEPTH: usize = MAX_DEPTH - UNFOLDED.len();
+
+/// Amphipods in each room, from the top row of the diagram down
+type Rooms = Vec<[u8; ROOMS]>;
+
+/// Every position of the burrow, first the hallway from left to right and then each room from
+/// the top down. Rooms shallower than `MAX_DEPTH` leave the last positions unused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct State([u8; HALLWAY + ROOMS * MAX_DEPTH]);
+
+impl State {
+    fn new(rooms: &Rooms) -> Self {
+        let mut cells = [EMPTY; HALLWAY + ROOMS * MAX_DEPTH];
+        for (depth, row) in rooms.iter().enumerate() {
+            for (room, &amphipod) in row.iter().enumerate() {
+                cells[slot(room, depth)] = amphipod;
+            }
+        }
+        State(cells)
+    }
+
+    fn room(&self, room: usize, depth: usize) -> &[u8] {
+        &self.0[slot(room, 0)..slot(room, depth)]
+    }
+}
+
+fn slot(room: usize, depth: usize) -> usize {
+    HALLWAY + room * MAX_DEPTH + depth
+}
+
+fn entrance(room: usize) -> usize {
+    2 + 2 * room
+}
+
+fn target_room(amphipod: u8) -> usize {
+    (amphipod - b'A') as usize
+}
+
+fn energy(amphipod: u8) -> u32 {
+    10u32.pow(target_room(amphipod) as u32)
+}
+
+/// Hallway positions strictly between `from` and `to`, and `to` itself
+fn hallway_path(from: usize, to: usize) -> impl Iterator<Item = usize> {
+    let (low, high) = if from < to {
+        (from + 1, to)
+    } else {
+        (to, from - 1)
+    };
+    low..=high
+}
+
+/// The burrow as a graph where each edge moves one amphipod and costs the energy it took
+struct Burrow {
+    depth: usize,
+}
+
+impl Burrow {
+    fn moves(&self, state: State) -> Vec<(State, u32)> {
+        let hallway = &state.0[..HALLWAY];
+        let is_clear = |from, to| hallway_path(from, to).all(|pos| hallway[pos] == EMPTY);
+        let mut moves = Vec::new();
+
+        // Amphipods in the hallway may only move into their own room, once it holds no strangers
+        for (pos, &amphipod) in hallway.iter().enumerate() {
+            if amphipod == EMPTY {
+                continue;
+            }
+            let room = target_room(amphipod);
+            let cells = state.room(room, self.depth);
+            if cells.iter().any(|&cell| cell != EMPTY && cell != amphipod) {
+                continue;
+            }
+            if !is_clear(pos, entrance(room)) {
+                continue;
+            }
+            let depth = cells.iter().rposition(|&cell| cell == EMPTY).unwrap();
+            let steps = pos.abs_diff(entrance(room)) + depth + 1;
+            let mut next = state;
+            next.0[pos] = EMPTY;
+            next.0[slot(room, depth)] = amphipod;
+            moves.push((next, steps as u32 * energy(amphipod)));
+        }
+
+        // The top amphipod of a room may move out into the hallway, unless it and everyone
+        // beneath it are already home
+        for room in 0..ROOMS {
+            let cells = state.room(room, self.depth);
+            let Some(depth) = cells.iter().position(|&cell| cell != EMPTY) else {
+                continue;
+            };
+            if cells[depth..].iter().all(|&cell| target_room(cell) == room) {
+                continue;
+            }
+            let amphipod = cells[depth];
+            for pos in HALLWAY_STOPS {
+                if is_clear(entrance(room), pos) {
+                    let steps = depth + 1 + pos.abs_diff(entrance(room));
+                    let mut next = state;
+                    next.0[slot(room, depth)] = EMPTY;
+                    next.0[pos] = amphipod;
+                    moves.push((next, steps as u32 * energy(amphipod)));
+                }
+            }
+        }
+        moves
+    }
+}
+
+impl Graph for Burrow {
+    type Node = State;
+    type Cost = u32;
+
+    fn neighbours(&self, node: State) -> impl Iterator<Item = (State, u32)> + '_ {
+        self.moves(node).into_iter()
+    }
+}
+
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<Rooms> {
+    parse_file(filename, parse)
+}
+
+/// Read the amphipods from the diagram row by row, ignoring the walls and the empty hallway
+pub fn parse(input: &str) -> Result<Rooms> {
+    let rows = input
+        .lines()
+        .skip(2) // Wall and hallway
+        .filter_map(|line| {
+            let amphipods = line.trim().trim_matches('#');
+            (!amphipods.is_empty()).then_some((line, amphipods))
+        })
+        .map(|(line, amphipods)| -> Result<[u8; ROOMS]> {
+            amphipods
+                .split('#')
+                .map(|a| match a {
+                    "A" | "B" | "C" | "D" => Some(a.as_bytes()[0]),
+                    _ => None,
+                })
+                .collect::<Option<Vec<u8>>>()
+                .and_then(|row| row.try_into().ok())
+                .ok_or_else(|| Error::at(input, line, "Expected a row like `#A#B#C#D#`"))
+        })
+        .collect::<Result<Rooms>>()?;
+    if rows.len() != FOLDED_DEPTH {
+        let reason = format!(
+            "Expected {} rows of amphipods, found {}",
+            FOLDED_DEPTH,
+            rows.len()
+        );
+        return Err(Error::at(input, input, reason));
+    }
+    // Every room must be able to fill up with its own type of amphipod
+    for kind in b'A'..=b'D' {
+        let count = rows.iter().flatten().filter(|&&a| a == kind).count();
+        if count != rows.len() {
+            let reason = format!("Expected {} amphipods of type {}", rows.len(), kind as char);
+            return Err(Error::at(input, input, reason));
+        }
+    }
+    Ok(rows)
+}
+
+/// Least energy needed to organize the amphipods, if they can be organized at all
+fn organize(rooms: &Rooms) -> Result<u32> {
+    let depth = rooms.len();
+    let goal = State::new(&vec![*b"ABCD"; depth]);
+    search::dijkstra(&Burrow { depth }, State::new(rooms), |state| state == goal)
+        .map(|path| path.cost)
+        .ok_or_else(|| Error::solve("The amphipods can't be organized"))
+}
+
+pub fn answer1(rooms: &Rooms) -> Result<u32> {
+    organize(rooms)
+}
+
+pub fn answer2(rooms: &Rooms) -> Result<u32> {
+    let unfolded = rooms[..1]
+        .iter()
+        .chain(&UNFOLDED)
+        .chain(&rooms[1..])
+        .copied()
+        .collect();
+    organize(&unfolded)
+}
+
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 23;
+    const DATA: &'static str = DATA;
+
+    type Input = Rooms;
+    type Answer1 = Result<u32>;
+    type Answer2 = Result<u32>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(&input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(&input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOCK_DATA: &str = concat!(
+        "#############\n",
+        "#...........#\n",
+        "###B#C#B#D###\n",
+        "  #A#D#C#A#\n",
+        "  #########\n",
+    );
+
+    #[test]
+    fn test_answer1_mock_data() {
+        assert_eq!(answer1(&parse(MOCK_DATA).unwrap()).unwrap(), 12521)
+    }
+
+    #[test]
+    fn test_answer2_mock_data() {
+        assert_eq!(answer2(&parse(MOCK_DATA).unwrap()).unwrap(), 44169)
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = parse(&MOCK_DATA.replace("#A#D", "#A#E")).unwrap_err();
+        assert!(matches!(err, Error::Parse { line: 4, .. }));
+        assert!(parse(&MOCK_DATA.replace("#A#D", "#A#A")).is_err());
+        // Already unfolded diagrams would no longer fit in the burrow for part 2
+        let unfolded = MOCK_DATA.replace("  #A", "  #D#C#B#A#\n  #A");
+        let err = parse(&unfolded).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Expected 2 rows of amphipods, found 3"));
+    }
+}
@@ -0,0 +1,384 @@
+use crate::{loaders::parse_file, Day, Error, Result};
+use itertools::Itertools;
+use std::str::FromStr;
+
+pub const DATA: &str = "input/aoc24";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Register(usize),
+    Number(i64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Inp(usize),
+    Add(usize, Operand),
+    Mul(usize, Operand),
+    Div(usize, Operand),
+    Mod(usize, Operand),
+    Eql(usize, Operand),
+}
+
+fn parse_register(register: &str) -> Option<usize> {
+    match register {
+        "w" => Some(0),
+        "x" => Some(1),
+        "y" => Some(2),
+        "z" => Some(3),
+        _ => None,
+    }
+}
+
+impl FromStr for Operand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_register(s) {
+            Some(register) => Ok(Operand::Register(register)),
+            None => s
+                .parse()
+                .map(Operand::Number)
+                .map_err(|_| format!("Expected a register or a number, found {:?}", s)),
+        }
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.split_whitespace().collect_vec();
+        let register = |name: &str| {
+            parse_register(name).ok_or_else(|| format!("Expected a register, found {:?}", name))
+        };
+        Ok(match parts[..] {
+            ["inp", a] => Instruction::Inp(register(a)?),
+            ["add", a, b] => Instruction::Add(register(a)?, b.parse()?),
+            ["mul", a, b] => Instruction::Mul(register(a)?, b.parse()?),
+            ["div", a, b] => Instruction::Div(register(a)?, b.parse()?),
+            ["mod", a, b] => Instruction::Mod(register(a)?, b.parse()?),
+            ["eql", a, b] => Instruction::Eql(register(a)?, b.parse()?),
+            _ => return Err("Expected an ALU instruction".into()),
+        })
+    }
+}
+
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<Monad> {
+    parse_file(filename, parse)
+}
+
+/// Each instruction together with its line
+fn instructions(input: &str) -> Result<Vec<(&str, Instruction)>> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let instruction = line.parse().map_err(|err| Error::at(input, line, err))?;
+            Ok((line, instruction))
+        })
+        .collect()
+}
+
+/// Parse any program for the ALU
+pub fn parse_program(input: &str) -> Result<Vec<Instruction>> {
+    Ok(instructions(input)?
+        .into_iter()
+        .map(|(_, instruction)| instruction)
+        .collect())
+}
+
+/// Run a program on the ALU, returning the `w`, `x`, `y` and `z` registers. Panics on division
+/// by zero or modulo of negative numbers, which the ALU does not allow.
+pub fn run(program: &[Instruction], input: impl IntoIterator<Item = i64>) -> [i64; 4] {
+    let mut input = input.into_iter();
+    let mut registers = [0; 4];
+    for &instruction in program {
+        let value = |operand| match operand {
+            Operand::Register(register) => registers[register],
+            Operand::Number(number) => number,
+        };
+        match instruction {
+            Instruction::Inp(a) => registers[a] = input.next().expect("Ran out of input"),
+            Instruction::Add(a, b) => registers[a] += value(b),
+            Instruction::Mul(a, b) => registers[a] *= value(b),
+            Instruction::Div(a, b) => registers[a] /= value(b),
+            Instruction::Mod(a, b) => {
+                let b = value(b);
+                assert!(registers[a] >= 0 && b > 0, "Invalid modulo");
+                registers[a] %= b;
+            }
+            Instruction::Eql(a, b) => registers[a] = (registers[a] == value(b)) as i64,
+        }
+    }
+    registers
+}
+
+/// Number of digits of a model number, MONAD has one block of instructions per digit
+const DIGITS: usize = 14;
+
+/// Block of MONAD, with empty lines in place of the parameters which differ between blocks
+const BLOCK_TEMPLATE: [&str; 18] = [
+    "inp w", "mul x 0", "add x z", "mod x 26", "", "", "eql x w", "eql x 0", "mul y 0", "add y 25",
+    "mul y x", "add y 1", "mul z y", "mul y 0", "add y w", "", "mul y x", "add z y",
+];
+
+/// Two digits which MONAD requires to satisfy `digit[pop] = digit[push] + diff`
+#[derive(Debug, Clone, Copy)]
+struct Pair {
+    push: usize,
+    pop: usize,
+    diff: i64,
+}
+
+/// MONAD, as the pairs of digits its blocks compare
+#[derive(Debug, Clone)]
+pub struct Monad {
+    pairs: Vec<Pair>,
+}
+
+/// Parse MONAD, pointing errors at the first instruction which doesn't fit its blocks.
+///
+/// Each block either pushes `digit + offset` onto a stack (encoded in base 26 in `z`, with
+/// `div z 1`), or pops the top value (with `div z 26`) and pushes again unless
+/// `top + check == digit`. `z` is only zero at the end if every pop block matches its push block,
+/// i.e. `digit[pop] = digit[push] + offset + check`.
+pub fn parse(input: &str) -> Result<Monad> {
+    let program = instructions(input)?;
+    if program.len() != DIGITS * BLOCK_TEMPLATE.len() {
+        let reason = format!(
+            "Expected MONAD of {} instructions, found {}",
+            DIGITS * BLOCK_TEMPLATE.len(),
+            program.len()
+        );
+        return Err(Error::at(input, input, reason));
+    }
+    let template: Vec<Option<Instruction>> = BLOCK_TEMPLATE
+        .iter()
+        .map(|line| line.parse().ok())
+        .collect();
+    let mut stack = Vec::new();
+    let mut pairs = Vec::new();
+    for (digit, block) in program.chunks(BLOCK_TEMPLATE.len()).enumerate() {
+        for ((&(line, instruction), expected), text) in
+            block.iter().zip(&template).zip(BLOCK_TEMPLATE)
+        {
+            if expected.is_some_and(|e| e != instruction) {
+                let reason = format!("Expected `{}` as in MONAD", text);
+                return Err(Error::at(input, line, reason));
+            }
+        }
+        let (div_line, pops) = match block[4] {
+            (line, Instruction::Div(3, Operand::Number(divisor @ (1 | 26)))) => {
+                (line, divisor == 26)
+            }
+            (line, _) => return Err(Error::at(input, line, "Expected `div z 1` or `div z 26`")),
+        };
+        let (check_line, check) = match block[5] {
+            (line, Instruction::Add(1, Operand::Number(check))) => (line, check),
+            (line, _) => return Err(Error::at(input, line, "Expected `add x` and a number")),
+        };
+        let offset = match block[15] {
+            (_, Instruction::Add(2, Operand::Number(offset))) => offset,
+            (line, _) => return Err(Error::at(input, line, "Expected `add y` and a number")),
+        };
+        if !pops {
+            stack.push((digit, offset, div_line));
+            continue;
+        }
+        let (push, offset, _) = stack.pop().ok_or_else(|| {
+            Error::at(
+                input,
+                div_line,
+                "Expected a digit to pop, MONAD pops more than it pushes",
+            )
+        })?;
+        // Both digits are between 1 and 9
+        let diff = offset + check;
+        if !(-8..=8).contains(&diff) {
+            let reason = format!("Expected digits to differ by at most 8, found {}", diff);
+            return Err(Error::at(input, check_line, reason));
+        }
+        pairs.push(Pair {
+            push,
+            pop: digit,
+            diff,
+        });
+    }
+    if let Some(&(_, _, line)) = stack.first() {
+        let reason = "Expected the digit to be popped, MONAD pushes more than it pops";
+        return Err(Error::at(input, line, reason));
+    }
+    Ok(Monad { pairs })
+}
+
+/// Find the largest or smallest model number accepted by MONAD. Every pair of digits can be
+/// chosen independently.
+fn model_number(monad: &Monad, largest: bool) -> u64 {
+    let mut digits = [0; DIGITS];
+    for &Pair { push, pop, diff } in &monad.pairs {
+        digits[push] = if largest {
+            9.min(9 - diff)
+        } else {
+            1.max(1 - diff)
+        };
+        digits[pop] = digits[push] + diff;
+    }
+    digits
+        .iter()
+        .fold(0, |number, &digit| number * 10 + digit as u64)
+}
+
+pub fn answer1(monad: &Monad) -> u64 {
+    model_number(monad, true)
+}
+
+pub fn answer2(monad: &Monad) -> u64 {
+    model_number(monad, false)
+}
+
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 24;
+    const DATA: &'static str = DATA;
+
+    type Input = Monad;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(&input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(&input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// MONAD with the given `(div z, add x, add y)` parameters for each block
+    fn mock_monad(parameters: [(i64, i64, i64); 14]) -> String {
+        parameters
+            .iter()
+            .flat_map(|&(divisor, check, offset)| {
+                BLOCK_TEMPLATE
+                    .iter()
+                    .enumerate()
+                    .map(move |(i, line)| match i {
+                        4 => format!("div z {}\n", divisor),
+                        5 => format!("add x {}\n", check),
+                        15 => format!("add y {}\n", offset),
+                        _ => format!("{}\n", line),
+                    })
+            })
+            .collect()
+    }
+
+    // Pairs (0, 13), (1, 4), (2, 3), (5, 12), (6, 7), (8, 11) and (9, 10)
+    const MOCK_PARAMETERS: [(i64, i64, i64); 14] = [
+        (1, 12, 4),
+        (1, 11, 11),
+        (1, 13, 5),
+        (26, -10, 7),
+        (26, -16, 3),
+        (1, 14, 5),
+        (1, 12, 7),
+        (26, -4, 6),
+        (1, 15, 2),
+        (1, 10, 12),
+        (26, -4, 1),
+        (26, -5, 9),
+        (26, -8, 4),
+        (26, -11, 8),
+    ];
+
+    fn digits(number: u64) -> Vec<i64> {
+        number
+            .to_string()
+            .chars()
+            .map(|c| c.to_digit(10).unwrap() as i64)
+            .collect()
+    }
+
+    #[test]
+    fn test_run() {
+        let negate = parse_program("inp x\nmul x -1\n").unwrap();
+        assert_eq!(run(&negate, [7])[1], -7);
+        let binary = parse_program(concat!(
+            "inp w\nadd z w\nmod z 2\ndiv w 2\nadd y w\nmod y 2\ndiv w 2\n",
+            "add x w\nmod x 2\ndiv w 2\nmod w 2\n",
+        ))
+        .unwrap();
+        assert_eq!(run(&binary, [11]), [1, 0, 1, 1]);
+    }
+
+    #[test]
+    fn test_answer1_mock_data() {
+        let input = mock_monad(MOCK_PARAMETERS);
+        let program = parse_program(&input).unwrap();
+        let answer = answer1(&parse(&input).unwrap());
+        assert_eq!(answer, 99_944_969_919_662);
+        assert_eq!(run(&program, digits(answer))[3], 0);
+        assert_ne!(run(&program, digits(answer + 1))[3], 0);
+    }
+
+    #[test]
+    fn test_answer2_mock_data() {
+        let input = mock_monad(MOCK_PARAMETERS);
+        let program = parse_program(&input).unwrap();
+        let answer = answer2(&parse(&input).unwrap());
+        assert_eq!(answer, 86_611_414_419_111);
+        assert_eq!(run(&program, digits(answer))[3], 0);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = parse_program("inp w\nadd q 1\n").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Parse {
+                line: 2,
+                column: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_monad_error() {
+        let error_line = |parameters: [(i64, i64, i64); 14]| match parse(&mock_monad(parameters)) {
+            Err(Error::Parse { line, reason, .. }) => (line, reason),
+            _ => panic!("Expected a parse error"),
+        };
+        let mut parameters = MOCK_PARAMETERS;
+        parameters[3].0 = 2;
+        assert_eq!(
+            error_line(parameters),
+            (3 * 18 + 5, "Expected `div z 1` or `div z 26`".into())
+        );
+        // The first block pops before anything was pushed
+        let mut parameters = MOCK_PARAMETERS;
+        parameters[0].0 = 26;
+        assert_eq!(error_line(parameters).0, 5);
+        // The last block pushes, leaving the first push without a pop
+        let mut parameters = MOCK_PARAMETERS;
+        parameters[13].0 = 1;
+        assert_eq!(error_line(parameters).0, 5);
+        // The digits of the pair (2, 3) would differ by 15
+        let mut parameters = MOCK_PARAMETERS;
+        parameters[3].1 = 10;
+        assert_eq!(error_line(parameters).0, 3 * 18 + 6);
+
+        let input = mock_monad(MOCK_PARAMETERS).replacen("eql x 0", "eql x 1", 1);
+        assert!(matches!(parse(&input), Err(Error::Parse { line: 8, .. })));
+        assert!(parse("inp w\n").is_err());
+    }
+}
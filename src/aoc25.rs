@@ -0,0 +1,143 @@
+use crate::{grid::Grid, loaders::parse_file, Day, Error, Result};
+use itertools::Itertools;
+
+pub const DATA: &str = "input/aoc25";
+
+const EAST: char = '>';
+const SOUTH: char = 'v';
+const EMPTY: char = '.';
+
+/// The sea floor, where herds leaving one edge reappear on the opposite one
+type SeaFloor = Grid<char>;
+
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<SeaFloor> {
+    parse_file(filename, parse)
+}
+
+pub fn parse(input: &str) -> Result<SeaFloor> {
+    let floor = Grid::parse_with(input, |c| match c {
+        EAST | SOUTH | EMPTY => Ok(c),
+        _ => Err("Expected `>`, `v` or `.`"),
+    })?;
+    Ok(floor.wrapping(true))
+}
+
+/// Move every sea cucumber of a herd which has room in front of it, returns whether any moved
+fn move_herd(floor: &mut SeaFloor, herd: char, direction: (isize, isize)) -> bool {
+    // Decide all moves before making any, since the herd moves simultaneously
+    let moves = floor
+        .indexed_iter()
+        .filter(|&(_, &cell)| cell == herd)
+        .filter_map(|(from, _)| {
+            let to = floor.offset(from, direction)?;
+            (floor[to] == EMPTY).then_some((from, to))
+        })
+        .collect_vec();
+    for &(from, to) in &moves {
+        floor[from] = EMPTY;
+        floor[to] = herd;
+    }
+    !moves.is_empty()
+}
+
+/// Move both herds, returns whether any sea cucumber moved
+fn step(floor: &mut SeaFloor) -> bool {
+    let east_moved = move_herd(floor, EAST, (0, 1));
+    let south_moved = move_herd(floor, SOUTH, (1, 0));
+    east_moved || south_moved
+}
+
+/// The first step on which no sea cucumber moves. Herds can also keep going around forever, which
+/// is caught by comparing each state with one saved at every power of two steps (Brent's cycle
+/// detection).
+pub fn answer1(mut floor: SeaFloor) -> Result<usize> {
+    let mut saved = floor.clone();
+    let mut steps: usize = 1;
+    while step(&mut floor) {
+        if floor == saved {
+            return Err(Error::solve("The sea cucumbers never stop moving"));
+        }
+        if steps.is_power_of_two() {
+            saved = floor.clone();
+        }
+        steps += 1;
+    }
+    Ok(steps)
+}
+
+/// There is no second puzzle on the last day, the star is given for finishing all the others
+pub fn answer2(_floor: SeaFloor) -> &'static str {
+    "Merry Christmas!"
+}
+
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 25;
+    const DATA: &'static str = DATA;
+
+    type Input = SeaFloor;
+    type Answer1 = Result<usize>;
+    type Answer2 = &'static str;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOCK_DATA: &str = concat!(
+        "v...>>.vv>\n",
+        ".vv>>.vv..\n",
+        ">>.>v>...v\n",
+        ">>v>>.>.v.\n",
+        "v>v.vv.v..\n",
+        ">.>>..v...\n",
+        ".vv..>.>v.\n",
+        "v.v..>>v.v\n",
+        "....v..v.>\n",
+    );
+
+    #[test]
+    fn test_step() {
+        let mut floor = parse("...>>>>>...").unwrap();
+        step(&mut floor);
+        assert_eq!(floor.to_string(), "...>>>>.>..");
+        step(&mut floor);
+        assert_eq!(floor.to_string(), "...>>>.>.>.");
+
+        let mut floor = parse("..........\n.>v....v..\n.......>..\n..........").unwrap();
+        step(&mut floor);
+        assert_eq!(
+            floor.to_string(),
+            "..........\n.>........\n..v....v>.\n.........."
+        );
+    }
+
+    #[test]
+    fn test_answer1_mock_data() {
+        assert_eq!(answer1(parse(MOCK_DATA).unwrap()).unwrap(), 58)
+    }
+
+    #[test]
+    fn test_never_stops() {
+        for input in [">.", "...>>.", "v\n.\n.", ">..\n..v\n..."] {
+            assert!(matches!(
+                answer1(parse(input).unwrap()),
+                Err(Error::Solve { .. })
+            ));
+        }
+        assert_eq!(answer1(parse(">>").unwrap()).unwrap(), 1);
+    }
+}
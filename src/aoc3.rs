@@ -1,26 +1,60 @@
-pub use crate::loaders::file_to_lines as load;
+use crate::{loaders::parse_file, Day, Error, Result};
 use std::{cmp, str};
 
 pub const DATA: &str = "input/aoc3";
 
-pub fn answer1(input: impl Iterator<Item = String>) -> i32 {
-    let mut input = input.peekable();
-    let mut len = 0;
-    // Count occurences of '1' for each position
-    let mut counts = vec![0; input.peek().unwrap().len()];
-    for line in input {
-        line.chars()
-            .zip(counts.iter_mut())
-            .for_each(|(bit, count)| match bit {
-                '1' => *count += 1,
-                '0' => (),
-                _ => panic!("Unexpected bit"),
-            });
-        len += 1;
+/// Widest numbers accepted, small enough that the product of two of them fits in an `i32`
+const MAX_WIDTH: usize = (i32::BITS / 2 - 1) as usize;
+
+/// The numbers of the diagnostic report, all with the same number of bits
+#[derive(Debug, Clone)]
+pub struct Report {
+    width: usize,
+    numbers: Vec<i32>,
+}
+
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<Report> {
+    parse_file(filename, parse)
+}
+
+pub fn parse(input: &str) -> Result<Report> {
+    let width = input.lines().next().map_or(0, str::len);
+    if width == 0 || width > MAX_WIDTH {
+        let reason = format!("Expected numbers of 1 to {} bits", MAX_WIDTH);
+        return Err(Error::at(input, input, reason));
     }
+    let numbers = input
+        .lines()
+        .map(|line| {
+            if let Some((i, c)) = line.char_indices().find(|&(_, c)| c != '0' && c != '1') {
+                let bit = &line[i..i + c.len_utf8()];
+                return Err(Error::at(input, bit, "Expected a bit of `0` or `1`"));
+            }
+            if line.len() != width {
+                let reason = format!("Expected {} bits, found {}", width, line.len());
+                return Err(Error::at(input, line, reason));
+            }
+            Ok(i32::from_str_radix(line, 2).expect("Only bits are left"))
+        })
+        .collect::<Result<_>>()?;
+    Ok(Report { width, numbers })
+}
+
+pub fn answer1(report: &Report) -> i32 {
+    // Count occurences of '1' for each position
+    let counts = (0..report.width)
+        .rev()
+        .map(|bit_pos| {
+            report
+                .numbers
+                .iter()
+                .filter(|&&n| (n >> bit_pos) & 0b1 == 0b1)
+                .count()
+        })
+        .collect::<Vec<_>>();
 
     // Build binary number
-    let majority_count = len / 2;
+    let majority_count = report.numbers.len() / 2;
     let gamma = counts.iter().fold(0, |num, bit| {
         if *bit > majority_count {
             (num << 1) | 1 // Add a 1
@@ -36,8 +70,8 @@ pub fn answer1(input: impl Iterator<Item = String>) -> i32 {
     gamma * epsilon
 }
 
-fn filter_value(mut all_nums: Vec<i32>, cmp: cmp::Ordering, start_bit: u32) -> i32 {
-    for bit_pos in (0..=start_bit).rev() {
+fn filter_value(mut all_nums: Vec<i32>, cmp: cmp::Ordering, bits: u32) -> Result<i32> {
+    for bit_pos in (0..bits).rev() {
         if all_nums.len() <= 1 {
             // Early exit
             break;
@@ -58,26 +92,50 @@ fn filter_value(mut all_nums: Vec<i32>, cmp: cmp::Ordering, start_bit: u32) -> i
         };
         all_nums.retain(|&s| (s >> bit_pos) & 0b1 != target_bit);
     }
-    if all_nums.len() != 1 {
-        panic!("Not exactly one number left after filter");
+    match all_nums[..] {
+        [number] => Ok(number),
+        _ => Err(Error::solve("Not exactly one number left after filter")),
     }
-    all_nums.pop().unwrap() // Only one item left
 }
 
-pub fn answer2(input: impl Iterator<Item = String>) -> i32 {
-    let input: Vec<i32> = input
-        .map(|line| i32::from_str_radix(&line, 2).unwrap())
-        .collect();
-    let bits_to_ignore = input.iter().map(|i| i.leading_zeros()).min().unwrap();
-    let start_bit = i32::BITS - 1 - bits_to_ignore;
-    filter_value(input.to_vec(), cmp::Ordering::Greater, start_bit)
-        * filter_value(input.to_vec(), cmp::Ordering::Less, start_bit)
+pub fn answer2(report: &Report) -> Result<i32> {
+    let input = &report.numbers;
+    let bits_to_ignore = input
+        .iter()
+        .map(|i| i.leading_zeros())
+        .min()
+        .unwrap_or(i32::BITS);
+    let bits = i32::BITS - bits_to_ignore;
+    Ok(filter_value(input.to_vec(), cmp::Ordering::Greater, bits)?
+        * filter_value(input.to_vec(), cmp::Ordering::Less, bits)?)
+}
+
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 3;
+    const DATA: &'static str = DATA;
+
+    type Input = Report;
+    type Answer1 = i32;
+    type Answer2 = Result<i32>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(&input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(&input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mockers::*;
 
     const MOCK_DATA: &str = "00100
 11110
@@ -94,21 +152,54 @@ mod tests {
 
     #[test]
     fn test_answer1_mock_data() {
-        assert_eq!(answer1(str_lines_to_string(MOCK_DATA)), 198)
+        assert_eq!(answer1(&parse(MOCK_DATA).unwrap()), 198)
     }
 
     #[test]
     fn test_answer2_mock_data() {
-        assert_eq!(answer2(str_lines_to_string(MOCK_DATA)), 230)
+        assert_eq!(answer2(&parse(MOCK_DATA).unwrap()).unwrap(), 230)
     }
 
     #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(load(DATA)), 4174964)
+    fn test_answer2_duplicates() {
+        assert!(matches!(
+            answer2(&parse("01\n01").unwrap()),
+            Err(Error::Solve { .. })
+        ));
+        assert_eq!(answer2(&parse("0").unwrap()).unwrap(), 0);
     }
 
     #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(load(DATA)), 4474944)
+    fn test_parse_error() {
+        let err = parse(&MOCK_DATA.replace("10111", "10x11")).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::Parse {
+                    line: 4,
+                    column: 3,
+                    ..
+                }
+            ),
+            "{}",
+            err
+        );
+        let err = parse(&MOCK_DATA.replace("10111", "1011")).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::Parse {
+                    line: 4,
+                    column: 1,
+                    ..
+                }
+            ),
+            "{}",
+            err
+        );
+        assert!(parse("").is_err());
+        let widest = "1".repeat(MAX_WIDTH);
+        assert_eq!(answer1(&parse(&widest).unwrap()), 0);
+        assert!(parse(&format!("{}1", widest)).is_err());
     }
 }
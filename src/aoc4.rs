@@ -1,89 +1,98 @@
-pub use crate::loaders::file_to_lines as load;
+use crate::{
+    grid::Grid,
+    loaders::{parse_at, parse_file},
+    Day, Error, Result,
+};
 use itertools::Itertools;
 
 pub const DATA: &str = "input/aoc4";
 
-type Grid<T, const LENGTH: usize> = [[T; LENGTH]; LENGTH];
+const BOARD_SIZE: usize = 5;
+type Bingo = (Vec<u8>, Vec<Board>); // Drawn numbers, and the boards
 
-#[derive(Default, Debug, Clone)]
+#[derive(Debug, Clone)]
 pub struct Board {
-    board: Grid<u8, 5>,
-    mask: Grid<bool, 5>,
-}
-
-fn check_row<const L: usize>(mask: Grid<bool, L>, i_row: usize) -> bool {
-    mask[i_row].iter().all(|&m| m)
-}
-
-fn check_column<const L: usize>(mask: Grid<bool, L>, i_column: usize) -> bool {
-    mask.iter().all(|row| row[i_column])
+    board: Grid<u8>,
+    mask: Grid<bool>,
 }
 
 impl Board {
     fn check_number(&mut self, number: u8) -> bool {
         // Assume only one occurence of each number per board
-        self.board.iter().enumerate().any(|(ir, row)| {
-            row.iter().enumerate().any(|(ic, col)| {
-                if *col == number {
-                    self.mask[ir][ic] = true;
-                    check_row(self.mask, ir) || check_column(self.mask, ic)
-                } else {
-                    false
-                }
-            })
-        })
+        match self.board.iter().position(|&n| n == number) {
+            Some(idx) => {
+                let (row, col) = self.board.point_of(idx);
+                self.mask[(row, col)] = true;
+                self.mask.row(row).iter().all(|&m| m) || self.mask.column(col).all(|&m| m)
+            }
+            None => false,
+        }
     }
 
     fn iter_unmasked(&self) -> impl Iterator<Item = u8> + '_ {
         self.board
             .iter()
-            .zip(self.mask)
-            .flat_map(|(row, mask_row)| row.iter().zip(mask_row))
+            .zip(self.mask.iter())
             .filter_map(|(board, mask)| if !mask { Some(*board) } else { None })
     }
 }
 
-pub fn parse_lines(mut lines: impl Iterator<Item = String>) -> (Vec<u8>, Vec<Board>) {
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<Bingo> {
+    parse_file(filename, parse)
+}
+
+pub fn parse(input: &str) -> Result<Bingo> {
+    let mut lines = input.lines();
     let order = lines
         .next()
-        .unwrap()
+        .ok_or_else(|| Error::at(input, input, "Missing bingo numbers"))?
         .split(',')
-        .map(|s| s.parse().unwrap())
-        .collect();
+        .map(|s| parse_at(input, s))
+        .try_collect()?;
     let boards = lines
         .chunks(6)
         .into_iter()
-        .map(|chunk| {
-            let mut board = Board::default();
-            for (input_row, row) in chunk.skip(1).zip(board.board.iter_mut()) {
-                for (input_col, col) in input_row.split_whitespace().zip(row.iter_mut()) {
-                    *col = input_col.parse().unwrap();
+        .map(|chunk| -> Result<Board> {
+            let mut numbers = Vec::with_capacity(BOARD_SIZE * BOARD_SIZE);
+            let mut rows = chunk.skip(1); // Each board is preceded by an empty line
+            for _ in 0..BOARD_SIZE {
+                let input_row = rows
+                    .next()
+                    .ok_or_else(|| Error::at(input, &input[input.len()..], "Incomplete board"))?;
+                let mut input_cols = input_row.split_whitespace();
+                for _ in 0..BOARD_SIZE {
+                    let input_col = input_cols
+                        .next()
+                        .ok_or_else(|| Error::at(input, input_row, "Too few numbers in row"))?;
+                    numbers.push(parse_at(input, input_col)?);
                 }
             }
-            board
+            Ok(Board {
+                board: Grid::from_vec(numbers, BOARD_SIZE),
+                mask: Grid::new(BOARD_SIZE, BOARD_SIZE, false),
+            })
         })
-        .collect();
-    (order, boards)
+        .try_collect()?;
+    Ok((order, boards))
 }
 
-pub fn answer1(input: impl Iterator<Item = String>) -> u32 {
-    find_first_winner(parse_lines(input))
+pub fn answer1(input: Bingo) -> Result<u32> {
+    find_first_winner(input)
 }
 
-fn find_first_winner((order, mut boards): (Vec<u8>, Vec<Board>)) -> u32 {
+fn find_first_winner((order, mut boards): Bingo) -> Result<u32> {
     // TODO: change order type to a trait bound instead to avoid a collect in answer2
     for number in order {
         for board in boards.iter_mut() {
             if board.check_number(number) {
-                return board.iter_unmasked().map_into::<u32>().sum::<u32>() * number as u32;
+                return Ok(board.iter_unmasked().map_into::<u32>().sum::<u32>() * number as u32);
             }
         }
     }
-    panic!("Game doesn't end, needs more numbers")
+    Err(Error::solve("Game doesn't end, needs more numbers"))
 }
 
-pub fn answer2(input: impl Iterator<Item = String>) -> u32 {
-    let (order, mut boards) = parse_lines(input);
+pub fn answer2((order, mut boards): Bingo) -> Result<u32> {
     let mut order = order.into_iter(); // Make a consumable iterator so that we can re-use it later
     for number in &mut order {
         boards = boards // could be replaced by retain_mut if using nightly
@@ -100,13 +109,37 @@ pub fn answer2(input: impl Iterator<Item = String>) -> u32 {
             return find_first_winner((order.collect(), boards));
         }
     }
-    panic!("Not enough bingo numbers to declare a slowest board")
+    Err(Error::solve(
+        "Not enough bingo numbers to declare a slowest board",
+    ))
+}
+
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 4;
+    const DATA: &'static str = DATA;
+
+    type Input = Bingo;
+    type Answer1 = Result<u32>;
+    type Answer2 = Result<u32>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mockers::*;
 
     const MOCK_DATA: &str = concat!(
         "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1\n",
@@ -132,21 +165,28 @@ mod tests {
 
     #[test]
     fn test_answer1_mock_data() {
-        assert_eq!(answer1(str_lines_to_string(MOCK_DATA)), 4512)
+        assert_eq!(answer1(parse(MOCK_DATA).unwrap()).unwrap(), 4512)
     }
 
     #[test]
     fn test_answer2_mock_data() {
-        assert_eq!(answer2(str_lines_to_string(MOCK_DATA)), 1924)
-    }
-
-    #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(load(DATA)), 31424)
+        assert_eq!(answer2(parse(MOCK_DATA).unwrap()).unwrap(), 1924)
     }
 
     #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(load(DATA)), 23042)
+    fn test_game_doesnt_end() {
+        let input = MOCK_DATA.replacen(
+            ",11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1",
+            "",
+            1,
+        );
+        assert!(matches!(
+            answer1(parse(&input).unwrap()),
+            Err(Error::Solve { .. })
+        ));
+        assert!(matches!(
+            answer2(parse(&input).unwrap()),
+            Err(Error::Solve { .. })
+        ));
     }
 }
@@ -1,4 +1,5 @@
-pub use crate::loaders::file_to as load;
+pub use crate::loaders::{file_to as load, str_to as parse};
+use crate::{Day, Result};
 use itertools::{EitherOrBoth, Itertools};
 use std::{collections::HashMap, error, str};
 
@@ -71,90 +72,53 @@ fn answer(input: impl Iterator<Item = Vent>, ignore_diagonal: bool) -> usize {
     grid.values().filter(|&&n| n >= 2).count()
 }
 
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 5;
+    const DATA: &'static str = DATA;
+
+    type Input = Vec<Vent>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(parse(input)?.collect())
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(input.into_iter())
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(input.into_iter())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const MOCK_DATA: &[Vent] = &[
-        Vent {
-            x1: 0,
-            y1: 9,
-            x2: 5,
-            y2: 9,
-        },
-        Vent {
-            x1: 8,
-            y1: 0,
-            x2: 0,
-            y2: 8,
-        },
-        Vent {
-            x1: 9,
-            y1: 4,
-            x2: 3,
-            y2: 4,
-        },
-        Vent {
-            x1: 2,
-            y1: 2,
-            x2: 2,
-            y2: 1,
-        },
-        Vent {
-            x1: 7,
-            y1: 0,
-            x2: 7,
-            y2: 4,
-        },
-        Vent {
-            x1: 6,
-            y1: 4,
-            x2: 2,
-            y2: 0,
-        },
-        Vent {
-            x1: 0,
-            y1: 9,
-            x2: 2,
-            y2: 9,
-        },
-        Vent {
-            x1: 3,
-            y1: 4,
-            x2: 1,
-            y2: 4,
-        },
-        Vent {
-            x1: 0,
-            y1: 0,
-            x2: 8,
-            y2: 8,
-        },
-        Vent {
-            x1: 5,
-            y1: 5,
-            x2: 8,
-            y2: 2,
-        },
-    ];
+    const MOCK_DATA: &str = concat!(
+        "0,9 -> 5,9\n",
+        "8,0 -> 0,8\n",
+        "9,4 -> 3,4\n",
+        "2,2 -> 2,1\n",
+        "7,0 -> 7,4\n",
+        "6,4 -> 2,0\n",
+        "0,9 -> 2,9\n",
+        "3,4 -> 1,4\n",
+        "0,0 -> 8,8\n",
+        "5,5 -> 8,2\n",
+    );
 
     #[test]
     fn test_answer1_mock_data() {
-        assert_eq!(answer1(MOCK_DATA.iter().cloned()), 5)
+        assert_eq!(answer1(parse(MOCK_DATA).unwrap()), 5)
     }
 
     #[test]
     fn test_answer2_mock_data() {
-        assert_eq!(answer2(MOCK_DATA.iter().cloned()), 12)
-    }
-
-    #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(load(DATA)), 8622)
-    }
-
-    #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(load(DATA)), 22037)
+        assert_eq!(answer2(parse(MOCK_DATA).unwrap()), 12)
     }
 }
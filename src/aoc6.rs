@@ -1,12 +1,36 @@
-use crate::loaders::delimited_file_to;
+use crate::{
+    loaders::{parse_at, parse_file},
+    Day, Error, Result,
+};
 
 pub const DATA: &str = "input/aoc6";
 const REPRODUCTIVE_PERIOD: usize = 7; // Days between offspring
 const PUBERTY_LENGTH: usize = 2; // Extra days before starting reproductive cycle
 
 #[inline]
-pub fn load(filename: impl AsRef<std::path::Path>) -> impl Iterator<Item = usize> {
-    delimited_file_to(filename, b',')
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<impl Iterator<Item = usize>> {
+    parse_file(filename, parse)
+}
+
+/// Parse the comma separated timers, which are never above that of a newborn fish
+pub fn parse(input: &str) -> Result<impl Iterator<Item = usize>> {
+    let timers: Vec<usize> = input
+        .trim()
+        .split(',')
+        .map(|s| {
+            let s = s.trim();
+            let timer = parse_at(input, s)?;
+            if timer >= REPRODUCTIVE_PERIOD + PUBERTY_LENGTH {
+                let reason = format!(
+                    "Expected a timer of at most {}",
+                    REPRODUCTIVE_PERIOD + PUBERTY_LENGTH - 1
+                );
+                return Err(Error::at(input, s, reason));
+            }
+            Ok(timer)
+        })
+        .collect::<Result<_>>()?;
+    Ok(timers.into_iter())
 }
 
 fn answer(input: impl Iterator<Item = usize>, days: usize) -> i64 {
@@ -29,6 +53,29 @@ pub fn answer2(input: impl Iterator<Item = usize>) -> i64 {
     answer(input, 256)
 }
 
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 6;
+    const DATA: &'static str = DATA;
+
+    type Input = Vec<usize>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(parse(input)?.collect())
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(input.into_iter())
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(input.into_iter())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -46,12 +93,17 @@ mod tests {
     }
 
     #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(load(DATA)), 361169)
-    }
-
-    #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(load(DATA)), 1634946868992)
+    fn test_parse_error() {
+        assert_eq!(parse("3,4,3,1,2\n").unwrap().collect::<Vec<_>>(), MOCK_DATA);
+        assert!(matches!(
+            parse("1,2,99").err(),
+            Some(Error::Parse {
+                line: 1,
+                column: 5,
+                ..
+            })
+        ));
+        assert!(parse("9").is_err());
+        assert!(parse("").is_err());
     }
 }
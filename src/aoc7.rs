@@ -1,14 +1,25 @@
-use crate::loaders::delimited_file_to;
-use std::{fmt::Debug, path::Path, str::FromStr};
+use crate::{
+    loaders::{delimited_file_to, delimited_str_to},
+    Day, Result,
+};
+use std::{fmt::Display, path::Path, str::FromStr};
 
 pub const DATA: &str = "input/aoc7";
 
-pub fn load<T>(filename: impl AsRef<Path>) -> Vec<T>
+pub fn load<T>(filename: impl AsRef<Path>) -> Result<Vec<T>>
 where
     T: FromStr,
-    T::Err: Debug,
+    T::Err: Display,
 {
-    delimited_file_to(filename, b',').collect::<Vec<T>>()
+    Ok(delimited_file_to(filename, ',')?.collect())
+}
+
+pub fn parse<T>(input: &str) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    Ok(delimited_str_to(input, ',')?.collect())
 }
 
 /// Since the problem is to find `min(sum(|pos-crab| over crab) over pos)`, which is the same as to
@@ -43,6 +54,29 @@ pub fn answer2(input: &[i32]) -> i32 {
         .unwrap()
 }
 
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 7;
+    const DATA: &'static str = DATA;
+
+    type Input = Vec<i32>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(mut input: Self::Input) -> Self::Answer1 {
+        answer1(&mut input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(&input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -58,14 +92,4 @@ mod tests {
     fn test_answer2_mock_data() {
         assert_eq!(answer2(&MOCK_DATA), 168)
     }
-
-    #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(&mut load(DATA)), 336040)
-    }
-
-    #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(&load(DATA)), 94813675)
-    }
 }
@@ -1,81 +1,143 @@
-pub use crate::loaders::file_to_lines as load;
+use crate::{loaders::parse_file, Day, Error, Result};
 use itertools::Itertools;
 use std::collections::HashSet;
 
 pub const DATA: &str = "input/aoc8";
 
-pub fn answer1(input: impl Iterator<Item = String>) -> usize {
-    input
-        .map(|line| {
-            line.split_once('|')
-                .unwrap()
-                .1 // only consider output
-                .split_whitespace()
-                .map(str::len)
-                .filter(|len| matches!(len, 2..=4 | 7)) // Count words with 2,3,4, or 7 characters
-                .count()
+/// The digits shown by the output of an entry, decoded from its ten patterns
+pub type Output = Vec<usize>;
+
+type Segments = HashSet<char>;
+
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<impl Iterator<Item = Output>> {
+    parse_file(filename, parse)
+}
+
+/// Parse and decode one entry per line, like `<ten patterns> | <four output digits>`
+pub fn parse(input: &str) -> Result<impl Iterator<Item = Output>> {
+    let outputs: Vec<Output> = input
+        .lines()
+        .map(|line| parse_entry(input, line))
+        .try_collect()?;
+    Ok(outputs.into_iter())
+}
+
+fn parse_entry(input: &str, line: &str) -> Result<Output> {
+    // Separate input and output
+    let (patterns, output) = line
+        .split_once('|')
+        .ok_or_else(|| Error::at(input, line, "Expected patterns and output separated by `|`"))?;
+    let patterns = digits(input, patterns, 10)?;
+    let numbers = decode(patterns.into_iter().map(|(_, digit)| digit))
+        .ok_or_else(|| Error::at(input, line, "Expected the patterns of the ten digits"))?;
+    digits(input, output, 4)?
+        .into_iter()
+        .map(|(word, digit)| {
+            numbers
+                .iter()
+                .position(|number| number == &digit)
+                .ok_or_else(|| Error::at(input, word, "Expected one of the ten patterns"))
         })
-        .sum()
+        .collect()
 }
 
-pub fn answer2(input: impl Iterator<Item = String>) -> usize {
-    input
-        .map(|line| {
-            // Separate input and output
-            let (input, output) = line.split_once('|').unwrap();
-
-            // Create map of number of segments to vector of sets of segements
-            let mut input = input
-                .split_whitespace()
-                .map(|digit| (digit.len(), digit.chars().collect::<HashSet<_>>()))
-                .into_group_map();
-
-            // Get the unique digits directly from length
-            let one = input.remove(&2).unwrap().swap_remove(0);
-            let four = input.remove(&4).unwrap().swap_remove(0);
-            let seven = input.remove(&3).unwrap().swap_remove(0);
-            let eight = input.remove(&7).unwrap().swap_remove(0);
-
-            // Calculate six segment digits
-            let six_len_digits = input.get_mut(&6).unwrap();
-            let nine = remove_by(six_len_digits, |digit| four.is_subset(digit)).unwrap(); // 4 is a subset of 9, but not 0 and 6
-            let zero = remove_by(six_len_digits, |digit| one.is_subset(digit)).unwrap(); // 1 is a subset 0, but not 6
-            let six = six_len_digits.swap_remove(0); // Only 6 left
-
-            // Calculate five segment digits
-            let five_len_digits = input.get_mut(&5).unwrap();
-            let three = remove_by(five_len_digits, |digit| one.is_subset(digit)).unwrap(); // 1 is a subset of 3, but not 2 and 5
-            let five = remove_by(five_len_digits, |digit| nine.is_superset(digit)).unwrap(); // 9 is superset of 5, but not 2
-            let two = five_len_digits.swap_remove(0); // Only 2 left
-
-            let numbers = [zero, one, two, three, four, five, six, seven, eight, nine];
-
-            // Calculate output number
-            output
-                .split_whitespace()
-                .map(|digit| digit.chars().collect::<HashSet<_>>())
-                .rev()
-                .enumerate()
-                .map(|(exp, digit)| {
-                    10usize.pow(exp as u32) * numbers.iter().position(|x| x == &digit).unwrap()
-                })
-                .sum::<usize>()
+/// The segments of each of the `count` digits of `words`, together with the word of each digit
+fn digits<'a>(input: &str, words: &'a str, count: usize) -> Result<Vec<(&'a str, Segments)>> {
+    let digits: Vec<_> = words
+        .split_whitespace()
+        .map(|word| {
+            let digit: Segments = word.chars().collect();
+            if digit.len() != word.len() || !digit.iter().all(|c| ('a'..='g').contains(c)) {
+                let reason = "Expected distinct segments between `a` and `g`";
+                return Err(Error::at(input, word, reason));
+            }
+            Ok((word, digit))
         })
-        .sum()
+        .try_collect()?;
+    if digits.len() != count {
+        let reason = format!("Expected {} digits, found {}", count, digits.len());
+        return Err(Error::at(input, words, reason));
+    }
+    Ok(digits)
+}
+
+/// Work out which pattern shows which digit, returns the patterns ordered by digit
+fn decode(patterns: impl Iterator<Item = Segments>) -> Option<[Segments; 10]> {
+    // Create map of number of segments to vector of sets of segements
+    let mut input = patterns.map(|digit| (digit.len(), digit)).into_group_map();
+    let mut unique = |len| match input.remove(&len) {
+        Some(mut digits) if digits.len() == 1 => digits.pop(),
+        _ => None,
+    };
+
+    // Get the unique digits directly from length
+    let one = unique(2)?;
+    let four = unique(4)?;
+    let seven = unique(3)?;
+    let eight = unique(7)?;
+
+    // Calculate six segment digits
+    let mut six_len_digits = input.remove(&6)?;
+    let nine = remove_by(&mut six_len_digits, |digit| four.is_subset(digit))?; // 4 is a subset of 9, but not 0 and 6
+    let zero = remove_by(&mut six_len_digits, |digit| one.is_subset(digit))?; // 1 is a subset 0, but not 6
+    let six = six_len_digits.pop()?; // Only 6 left
+
+    // Calculate five segment digits
+    let mut five_len_digits = input.remove(&5)?;
+    let three = remove_by(&mut five_len_digits, |digit| one.is_subset(digit))?; // 1 is a subset of 3, but not 2 and 5
+    let five = remove_by(&mut five_len_digits, |digit| nine.is_superset(digit))?; // 9 is superset of 5, but not 2
+    let two = five_len_digits.pop()?; // Only 2 left
+
+    Some([zero, one, two, three, four, five, six, seven, eight, nine])
 }
 
-fn remove_by<P, I>(array: &mut Vec<I>, predicate: P) -> Result<I, &str>
+fn remove_by<P, I>(array: &mut Vec<I>, predicate: P) -> Option<I>
 where
     P: FnMut(&I) -> bool,
 {
-    let idx = array.iter().position(predicate).ok_or("No item found")?;
-    Ok(array.swap_remove(idx))
+    let idx = array.iter().position(predicate)?;
+    Some(array.swap_remove(idx))
+}
+
+pub fn answer1(input: impl Iterator<Item = Output>) -> usize {
+    input
+        .flatten()
+        .filter(|digit| matches!(digit, 1 | 4 | 7 | 8)) // Count the digits with a unique number of segments
+        .count()
+}
+
+pub fn answer2(input: impl Iterator<Item = Output>) -> usize {
+    input
+        .map(|output| output.iter().fold(0, |number, digit| 10 * number + digit))
+        .sum()
+}
+
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 8;
+    const DATA: &'static str = DATA;
+
+    type Input = Vec<Output>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(parse(input)?.collect())
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(input.into_iter())
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(input.into_iter())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mockers::*;
 
     const MOCK_DATA: &str = concat!(
         "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb |        ",
@@ -102,21 +164,27 @@ mod tests {
 
     #[test]
     fn test_answer1_mock_data() {
-        assert_eq!(answer1(str_lines_to_string(MOCK_DATA)), 26)
+        assert_eq!(answer1(parse(MOCK_DATA).unwrap()), 26)
     }
 
     #[test]
     fn test_answer2_mock_data() {
-        assert_eq!(answer2(str_lines_to_string(MOCK_DATA)), 61229)
-    }
-
-    #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(load(DATA)), 488)
+        assert_eq!(answer2(parse(MOCK_DATA).unwrap()), 61229)
     }
 
     #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(load(DATA)), 1040429)
+    fn test_parse_error() {
+        let line = MOCK_DATA.lines().next().unwrap();
+        let error = |line: &str| match parse(line) {
+            Err(Error::Parse { column, reason, .. }) => (column, reason),
+            _ => panic!("Expected a parse error for {}", line),
+        };
+        assert_eq!(error(&line.replace('|', "")).0, 1);
+        assert_eq!(error(&line.replace("cfbegad", "cfbegax")).0, 4);
+        assert_eq!(error(&line.replace("fdgacbe", "fdgacb")).0, 69);
+        assert_eq!(
+            error(&line.replace("edb", "ed")).1,
+            "Expected the patterns of the ten digits"
+        );
     }
 }
@@ -1,39 +1,27 @@
-pub use crate::loaders::file_to_array2 as load;
-use ndarray::Array2;
+use crate::{
+    grid::{Grid, Point},
+    loaders::parse_file,
+    Day, Error, Result,
+};
 pub const DATA: &str = "input/aoc9";
 
-type Map = Array2<u32>;
+type Map = Grid<u32>;
 
-fn iter_low_points(floor: &Map) -> impl Iterator<Item = ((usize, usize), &u32)> {
-    let (n_row, n_col) = floor.dim();
-    floor.indexed_iter().filter(move |&((i_row, i_col), &pos)| {
-        iter_neighbours(i_row, i_col, n_row - 1, n_col - 1).all(|idx| floor[idx] > pos)
-    })
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<Map> {
+    parse_file(filename, parse)
 }
 
-fn iter_neighbours(
-    row: usize,
-    col: usize,
-    max_row: usize,
-    max_col: usize,
-) -> impl Iterator<Item = (usize, usize)> {
-    let mut v = Vec::with_capacity(4);
-    if row > 0 {
-        v.push((row - 1, col));
-    }
-    if row < max_row {
-        v.push((row + 1, col));
-    }
-    if col > 0 {
-        v.push((row, col - 1));
-    }
-    if col < max_col {
-        v.push((row, col + 1));
-    }
-    v.into_iter()
+pub fn parse(input: &str) -> Result<Map> {
+    Grid::parse_digits(input)
+}
+
+fn iter_low_points(floor: &Map) -> impl Iterator<Item = (Point, &u32)> {
+    floor
+        .indexed_iter()
+        .filter(move |&(point, &height)| floor.neighbours4(point).all(|idx| floor[idx] > height))
 }
 
-fn find_unclaimed(claim_mask: &Array2<bool>) -> Option<(usize, usize)> {
+fn find_unclaimed(claim_mask: &Grid<bool>) -> Option<Point> {
     claim_mask
         .indexed_iter()
         .find_map(|(idx, &val)| if !val { Some(idx) } else { None })
@@ -44,19 +32,17 @@ pub fn answer1(input: &Map) -> u32 {
     iter_low_points(input).map(|(_, i)| i + 1).sum()
 }
 
-pub fn answer2(floor: &Map) -> usize {
+pub fn answer2(floor: &Map) -> Result<usize> {
     // Map to keep track of cells to not check, starts as all the edges
     let mut claimed = floor.map(|&height| height == 9);
-    let (n_row, n_col) = floor.dim();
-    let (max_row, max_col) = (n_row - 1, n_col - 1);
     let mut basins = Vec::new();
 
     while let Some(idx) = find_unclaimed(&claimed) {
         claimed[idx] = true;
         let mut basin = 1;
         let mut new_cells = vec![idx];
-        while let Some((row, col)) = new_cells.pop() {
-            new_cells.extend(iter_neighbours(row, col, max_row, max_col).filter(|&idx| {
+        while let Some(point) = new_cells.pop() {
+            new_cells.extend(floor.neighbours4(point).filter(|&idx| {
                 if claimed[idx] {
                     return false; // Only care for unclaimed cells
                 }
@@ -67,14 +53,39 @@ pub fn answer2(floor: &Map) -> usize {
         }
         basins.push(basin);
     }
+    if basins.len() < 3 {
+        return Err(Error::solve("Expected at least three basins"));
+    }
     basins.sort();
-    basins[basins.len() - 3..].iter().product()
+    Ok(basins[basins.len() - 3..].iter().product())
+}
+
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 9;
+    const DATA: &'static str = DATA;
+
+    type Input = Map;
+    type Answer1 = u32;
+    type Answer2 = Result<usize>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(&input)
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(&input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ndarray::arr2;
 
     const MOCK_DATA: [[u32; 10]; 5] = [
         [2, 1, 9, 9, 9, 4, 3, 2, 1, 0],
@@ -84,23 +95,25 @@ mod tests {
         [9, 8, 9, 9, 9, 6, 5, 6, 7, 8],
     ];
 
-    #[test]
-    fn test_answer1_mock_data() {
-        assert_eq!(answer1(&arr2(&MOCK_DATA)), 15)
+    fn mock_map() -> Map {
+        Grid::from_vec(MOCK_DATA.concat(), MOCK_DATA[0].len())
     }
 
     #[test]
-    fn test_answer2_mock_data() {
-        assert_eq!(answer2(&arr2(&MOCK_DATA)), 1134)
+    fn test_answer1_mock_data() {
+        assert_eq!(answer1(&mock_map()), 15)
     }
 
     #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(&load(DATA)), 554)
+    fn test_answer2_mock_data() {
+        assert_eq!(answer2(&mock_map()).unwrap(), 1134)
     }
 
     #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(&load(DATA)), 1017792)
+    fn test_too_few_basins() {
+        assert!(matches!(
+            answer2(&parse("191\n999").unwrap()),
+            Err(Error::Solve { .. })
+        ));
     }
 }
@@ -1,40 +1,60 @@
+use crate::{loaders::parse_file, Day, Result};
+
 pub const DATA: &str = "input/placeholder";
 
-pub fn load(data: &str) -> &str {
-    todo!()
+pub fn load(filename: impl AsRef<std::path::Path>) -> Result<impl Iterator<Item = String>> {
+    parse_file(filename, parse)
 }
 
-pub fn answer1(input: &str) -> usize {
+pub fn parse(input: &str) -> Result<impl Iterator<Item = String>> {
+    let lines: Vec<String> = input.lines().map(str::to_owned).collect();
+    Ok(lines.into_iter())
+}
+
+pub fn answer1(input: impl Iterator<Item = String>) -> usize {
     todo!()
 }
 
-pub fn answer2(input: &str) -> usize {
+pub fn answer2(input: impl Iterator<Item = String>) -> usize {
     todo!()
 }
 
+pub struct Puzzle;
+
+impl Day for Puzzle {
+    const DAY: u8 = 0;
+    const DATA: &'static str = DATA;
+
+    type Input = Vec<String>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(parse(input)?.collect())
+    }
+
+    fn part1(input: Self::Input) -> Self::Answer1 {
+        answer1(input.into_iter())
+    }
+
+    fn part2(input: Self::Input) -> Self::Answer2 {
+        answer2(input.into_iter())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const MOCK_DATA: &str = DATA;
+    const MOCK_DATA: &str = "";
 
     #[test]
     fn test_answer1_mock_data() {
-        assert_eq!(answer1(MOCK_DATA), 7)
+        assert_eq!(answer1(parse(MOCK_DATA).unwrap()), 7)
     }
 
     #[test]
     fn test_answer2_mock_data() {
-        assert_eq!(answer2(MOCK_DATA), 5)
-    }
-
-    #[test]
-    fn test_answer1() {
-        assert_eq!(answer1(load(DATA)), 0)
-    }
-
-    #[test]
-    fn test_answer2() {
-        assert_eq!(answer2(load(DATA)), 0)
+        assert_eq!(answer2(parse(MOCK_DATA).unwrap()), 5)
     }
 }
@@ -0,0 +1,224 @@
+use crate::{loaders::parse_at, Error, Part, Result};
+use std::{
+    fmt::{self, Display},
+    hint::black_box,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// The individually timed steps of solving a day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Step {
+    Parse,
+    Part(Part),
+}
+
+impl Step {
+    pub const ALL: [Step; 3] = [Step::Parse, Step::Part(Part::One), Step::Part(Part::Two)];
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Parse => write!(f, "parse"),
+            Step::Part(part) => write!(f, "part{}", part),
+        }
+    }
+}
+
+impl FromStr for Step {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "parse" => Ok(Step::Parse),
+            _ => match s.strip_prefix("part") {
+                Some(part) => Ok(Step::Part(part.parse()?)),
+                None => Err(format!("No such step: {}", s)),
+            },
+        }
+    }
+}
+
+/// Limits on how long to keep sampling a single step
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    /// Keep sampling until this much time has been spent on the step ...
+    pub budget: Duration,
+    /// ... but always take at least this many samples
+    pub min_samples: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            budget: Duration::from_millis(200),
+            min_samples: 5,
+        }
+    }
+}
+
+/// Run `setup` and then time `routine` on its output repeatedly, returning the median time.
+/// Only `routine` is timed, which allows e.g. cloning the input outside of the measurement.
+pub fn measure<I, O>(
+    config: &Config,
+    mut setup: impl FnMut() -> I,
+    mut routine: impl FnMut(I) -> O,
+) -> Duration {
+    black_box(routine(setup())); // Warm up
+    let mut samples = Vec::with_capacity(config.min_samples);
+    let start = Instant::now();
+    while samples.len() < config.min_samples || start.elapsed() < config.budget {
+        let input = setup();
+        let sample_start = Instant::now();
+        let output = routine(black_box(input));
+        samples.push(sample_start.elapsed());
+        drop(black_box(output));
+    }
+    samples.sort_unstable();
+    samples[samples.len() / 2]
+}
+
+/// Median time of a single step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement {
+    pub day: u8,
+    pub step: Step,
+    pub time: Duration,
+}
+
+/// A set of measurements, e.g. of a full benchmark run, which can be saved as and loaded from a
+/// baseline with one `<day> <step> <nanoseconds>` line per measurement
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    pub measurements: Vec<Measurement>,
+}
+
+impl Report {
+    pub fn get(&self, day: u8, step: Step) -> Option<Duration> {
+        self.measurements
+            .iter()
+            .find(|m| m.day == day && m.step == step)
+            .map(|m| m.time)
+    }
+
+    pub fn to_baseline(&self) -> String {
+        self.measurements
+            .iter()
+            .map(|m| format!("{} {} {}\n", m.day, m.step, m.time.as_nanos()))
+            .collect()
+    }
+
+    pub fn from_baseline(input: &str) -> Result<Self> {
+        let measurements = input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let fields: Vec<_> = line.split_whitespace().collect();
+                match fields[..] {
+                    [day, step, nanos] => Ok(Measurement {
+                        day: parse_at(input, day)?,
+                        step: parse_at(input, step)?,
+                        time: Duration::from_nanos(parse_at(input, nanos)?),
+                    }),
+                    _ => Err(Error::at(
+                        input,
+                        line,
+                        "Expected `<day> <step> <nanoseconds>`",
+                    )),
+                }
+            })
+            .collect::<Result<_>>()?;
+        Ok(Report { measurements })
+    }
+
+    /// Measurements which are more than `threshold` (e.g. 0.1 for 10%) slower than in `baseline`
+    pub fn regressions<'a>(
+        &'a self,
+        baseline: &'a Report,
+        threshold: f64,
+    ) -> impl Iterator<Item = &'a Measurement> + 'a {
+        self.measurements.iter().filter(move |m| {
+            baseline
+                .get(m.day, m.step)
+                .is_some_and(|base| relative_change(base, m.time) > threshold)
+        })
+    }
+
+    /// Summary table of all measurements, compared to `baseline` if there is one
+    pub fn table(&self, baseline: Option<&Report>) -> String {
+        let mut table = format!("{:>3}  {:<6} {:>12}", "Day", "Step", "Median");
+        if baseline.is_some() {
+            table += &format!(" {:>12} {:>8}", "Baseline", "Change");
+        }
+        table.push('\n');
+        for m in &self.measurements {
+            table += &format!("{:>3}  {:<6} {:>12.3?}", m.day, m.step.to_string(), m.time);
+            match baseline.map(|baseline| baseline.get(m.day, m.step)) {
+                Some(Some(base)) => {
+                    let change = 100. * relative_change(base, m.time);
+                    table += &format!(" {:>12.3?} {:>+7.1}%", base, change);
+                }
+                Some(None) => table += &format!(" {:>12} {:>8}", "-", "-"),
+                None => (),
+            }
+            table.push('\n');
+        }
+        let total: Duration = self.measurements.iter().map(|m| m.time).sum();
+        table += &format!("{:>3}  {:<6} {:>12.3?}\n", "", "total", total);
+        table
+    }
+}
+
+fn relative_change(before: Duration, after: Duration) -> f64 {
+    after.as_secs_f64() / before.as_secs_f64() - 1.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_report(times: [u64; 3]) -> Report {
+        Report {
+            measurements: Step::ALL
+                .into_iter()
+                .zip(times)
+                .map(|(step, time)| Measurement {
+                    day: 15,
+                    step,
+                    time: Duration::from_micros(time),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_baseline_round_trip() {
+        let report = mock_report([50, 3000, 90000]);
+        assert_eq!(
+            Report::from_baseline(&report.to_baseline()).unwrap(),
+            report
+        );
+        assert!(Report::from_baseline("15 part3 100\n").is_err());
+    }
+
+    #[test]
+    fn test_regressions() {
+        let baseline = mock_report([50, 3000, 90000]);
+        let report = mock_report([51, 3000, 120000]);
+        let regressions = report.regressions(&baseline, 0.1).collect::<Vec<_>>();
+        assert_eq!(regressions, [&report.measurements[2]]);
+        assert!(report.table(Some(&baseline)).contains("+33.3%"));
+    }
+
+    #[test]
+    fn test_measure() {
+        let config = Config {
+            budget: Duration::ZERO,
+            min_samples: 3,
+        };
+        let mut calls = 0;
+        measure(&config, || 1, |x| calls += x);
+        assert_eq!(calls, 4); // Warm up + samples
+    }
+}
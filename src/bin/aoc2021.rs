@@ -0,0 +1,420 @@
+use aoc2021::{
+    bench::{self, Report},
+    fetch::{self, cookie_from_env, Fetched, Fetcher},
+    file_to_string, get_day, reader_to_string,
+    verify::{Answers, Outcome, ANSWERS},
+    Part, Result, Solution, DAYS,
+};
+use std::{
+    env, fs,
+    io::stdin,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Duration,
+};
+
+const USAGE: &str = "\
+Usage:
+    aoc2021 run <DAY|all> [--part <1|2>] [--input <PATH>]
+    aoc2021 bench [DAY|all] [--baseline <PATH>] [--save-baseline <PATH>] [--threshold <PERCENT>]
+                  [--budget <MS>]
+    aoc2021 verify [DAY|all] [--answers <PATH>]
+    aoc2021 fetch <DAY|all> [--base-url <URL>] [--dir <PATH>] [--interval <SECONDS>]
+
+Commands:
+    run     Solve the puzzle of a given day (or every implemented day in order) and print the
+            answers along with the time it took to parse the input and to solve each part.
+    bench   Time parsing and solving both parts of a given day (or every day by default) and
+            print a summary table.
+    verify  Solve every registered input of a given day (or of every day by default) and compare
+            the answers against the expected ones, reporting any mismatches.
+    fetch   Download the input of a given day (or of every day) unless it is already cached.
+            Requires the session cookie in the COOKIE environment variable or in .env.
+
+Run options:
+    --part <1|2>             Only solve the given part, both parts are solved by default
+    --input <PATH>           Puzzle input to use instead of the default one in input/, use `-`
+                             to read from stdin. Not valid with `all`
+
+Bench options:
+    --baseline <PATH>        Compare against a saved baseline, failing on regressions
+    --save-baseline <PATH>   Save the measurements as a new baseline
+    --threshold <PERCENT>    How much slower than the baseline a step may be [default: 10]
+    --budget <MS>            Time to spend sampling each step [default: 200]
+
+Verify options:
+    --answers <PATH>         Registry of inputs and expected answers [default: answers.toml]
+
+Fetch options:
+    --base-url <URL>         URL of the event [default: https://adventofcode.com/2021]
+    --dir <PATH>             Directory to cache the inputs in [default: input]
+    --interval <SECONDS>     Minimum time between two requests [default: 5]";
+
+/// Days which have a puzzle, implemented or not
+const ALL_DAYS: std::ops::RangeInclusive<u8> = 1..=25;
+
+enum Days {
+    All,
+    Single(u8),
+}
+
+impl Days {
+    fn parse(arg: &str) -> Result<Self, String> {
+        match arg {
+            "all" => Ok(Days::All),
+            day => Ok(Days::Single(
+                day.parse().map_err(|_| format!("Invalid day: {}", day))?,
+            )),
+        }
+    }
+}
+
+struct RunArgs {
+    days: Days,
+    parts: Vec<Part>,
+    input: Option<PathBuf>,
+}
+
+fn parse_run_args(mut args: impl Iterator<Item = String>) -> Result<RunArgs, String> {
+    let days = Days::parse(&args.next().ok_or("Missing day")?)?;
+    let mut parts = Part::BOTH.to_vec();
+    let mut input = None;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {}", arg));
+        match arg.as_str() {
+            "--part" | "-p" => parts = vec![value()?.parse()?],
+            "--input" | "-i" => input = Some(value()?.into()),
+            _ => return Err(format!("Unexpected argument: {}", arg)),
+        }
+    }
+    if matches!(days, Days::All) && input.is_some() {
+        return Err("--input cannot be used together with all".into());
+    }
+    Ok(RunArgs { days, parts, input })
+}
+
+const STDIN: &str = "-";
+
+/// Read the whole puzzle input from a file, or from stdin if the path is `-`
+fn read_input(path: &Path) -> Result<String> {
+    if path == Path::new(STDIN) {
+        reader_to_string(stdin().lock())
+    } else {
+        file_to_string(path)
+    }
+}
+
+/// Whether the default input of a day has been fetched, days without one are skipped with a note
+/// when running all of them
+fn has_input(day: &dyn Solution) -> bool {
+    let found = Path::new(day.data()).exists();
+    if !found {
+        eprintln!("Day {:>2}: skipped, no input at {}", day.day(), day.data());
+    }
+    found
+}
+
+/// Solve and print the requested parts of a single day, returns false if any part failed
+fn run_day(day: &dyn Solution, parts: &[Part], input: Option<&PathBuf>) -> bool {
+    let path = input.map_or_else(|| Path::new(day.data()), PathBuf::as_path);
+    let name = if path == Path::new(STDIN) {
+        Path::new("<stdin>")
+    } else {
+        path
+    };
+    let input = match read_input(path).map_err(|err| err.with_path(name)) {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("Day {:>2}: {}", day.day(), err);
+            return false;
+        }
+    };
+    let mut success = true;
+    for &part in parts {
+        match day.run(&input, part).map_err(|err| err.with_path(name)) {
+            Ok(run) => println!(
+                "Day {:>2} part {}: {:<20} (parse {:>10.3?}, solve {:>10.3?})",
+                day.day(),
+                part,
+                run.answer,
+                run.parse_time,
+                run.solve_time
+            ),
+            Err(err) => {
+                eprintln!("Day {:>2} part {}: {}", day.day(), part, err);
+                success = false;
+            }
+        }
+    }
+    success
+}
+
+fn run(args: RunArgs) -> ExitCode {
+    let success = match args.days {
+        Days::All => {
+            let mut success = true;
+            for day in DAYS.iter().filter(|day| has_input(**day)) {
+                success &= run_day(*day, &args.parts, None); // Keep going after a failed day
+            }
+            success
+        }
+        Days::Single(day) => match get_day(day) {
+            Some(day) => run_day(day, &args.parts, args.input.as_ref()),
+            None => {
+                eprintln!("Day {} is not implemented", day);
+                false
+            }
+        },
+    };
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+struct BenchArgs {
+    days: Days,
+    baseline: Option<PathBuf>,
+    save_baseline: Option<PathBuf>,
+    threshold: f64,
+    config: bench::Config,
+}
+
+fn parse_bench_args(mut args: impl Iterator<Item = String>) -> Result<BenchArgs, String> {
+    let mut bench_args = BenchArgs {
+        days: Days::All,
+        baseline: None,
+        save_baseline: None,
+        threshold: 0.1,
+        config: bench::Config::default(),
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {}", arg));
+        let invalid = |value| format!("Invalid value for {}: {}", arg, value);
+        match arg.as_str() {
+            "--baseline" => bench_args.baseline = Some(value()?.into()),
+            "--save-baseline" => bench_args.save_baseline = Some(value()?.into()),
+            "--threshold" => {
+                let value = value()?;
+                let percent: f64 = value
+                    .parse()
+                    .ok()
+                    .filter(|percent: &f64| percent.is_finite() && *percent >= 0.)
+                    .ok_or_else(|| invalid(value))?;
+                bench_args.threshold = percent / 100.;
+            }
+            "--budget" => {
+                let value = value()?;
+                let millis = value.parse().map_err(|_| invalid(value))?;
+                bench_args.config.budget = Duration::from_millis(millis);
+            }
+            days if !days.starts_with('-') => bench_args.days = Days::parse(days)?,
+            _ => return Err(format!("Unexpected argument: {}", arg)),
+        }
+    }
+    Ok(bench_args)
+}
+
+fn bench(args: BenchArgs) -> ExitCode {
+    let days = match args.days {
+        Days::All => DAYS.iter().copied().filter(|day| has_input(*day)).collect(),
+        Days::Single(day) => match get_day(day) {
+            Some(day) => vec![day],
+            None => {
+                eprintln!("Day {} is not implemented", day);
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+    let baseline = match args.baseline.as_ref().map(|path| {
+        Report::from_baseline(&file_to_string(path)?).map_err(|err| err.with_path(path))
+    }) {
+        Some(Err(err)) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+        baseline => baseline.and_then(Result::ok),
+    };
+
+    let mut success = true;
+    let mut report = Report::default();
+    for day in days {
+        let measurements = file_to_string(day.data()).and_then(|input| {
+            day.bench(&input, &args.config)
+                .map_err(|err| err.with_path(day.data()))
+        });
+        match measurements {
+            Ok(measurements) => report.measurements.extend(measurements),
+            Err(err) => {
+                eprintln!("Day {:>2}: {}", day.day(), err);
+                success = false;
+            }
+        }
+    }
+    print!("{}", report.table(baseline.as_ref()));
+
+    if let Some(baseline) = &baseline {
+        for regression in report.regressions(baseline, args.threshold) {
+            eprintln!(
+                "Day {:>2} {} regressed by more than {}%",
+                regression.day,
+                regression.step,
+                100. * args.threshold
+            );
+            success = false;
+        }
+    }
+    if let Some(path) = &args.save_baseline {
+        if let Err(err) = fs::write(path, report.to_baseline()) {
+            eprintln!("{}: {}", path.display(), err);
+            success = false;
+        }
+    }
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+struct VerifyArgs {
+    days: Days,
+    answers: PathBuf,
+}
+
+fn parse_verify_args(mut args: impl Iterator<Item = String>) -> Result<VerifyArgs, String> {
+    let mut verify_args = VerifyArgs {
+        days: Days::All,
+        answers: ANSWERS.into(),
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {}", arg));
+        match arg.as_str() {
+            "--answers" => verify_args.answers = value()?.into(),
+            days if !days.starts_with('-') => verify_args.days = Days::parse(days)?,
+            _ => return Err(format!("Unexpected argument: {}", arg)),
+        }
+    }
+    Ok(verify_args)
+}
+
+fn verify(args: VerifyArgs) -> ExitCode {
+    let answers = match Answers::load(&args.answers) {
+        Ok(answers) => answers,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let checks = answers.verify(match args.days {
+        Days::All => None,
+        Days::Single(day) => Some(day),
+    });
+    let mut correct = 0;
+    for check in &checks {
+        let line = format!(
+            "Day {:>2} part {} ({}): {}",
+            check.day,
+            check.part,
+            check.path.display(),
+            check.outcome
+        );
+        if check.outcome == Outcome::Correct {
+            correct += 1;
+            println!("{}", line);
+        } else {
+            eprintln!("{}", line);
+        }
+    }
+    println!("{}/{} answers correct", correct, checks.len());
+    if correct == checks.len() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+struct FetchArgs {
+    days: Days,
+    config: fetch::Config,
+}
+
+fn parse_fetch_args(mut args: impl Iterator<Item = String>) -> Result<FetchArgs, String> {
+    let days = Days::parse(&args.next().ok_or("Missing day")?)?;
+    let mut config = fetch::Config {
+        cookie: cookie_from_env(),
+        ..Default::default()
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {}", arg));
+        let invalid = |value| format!("Invalid value for {}: {}", arg, value);
+        match arg.as_str() {
+            "--base-url" => config.base_url = value()?,
+            "--dir" => config.dir = value()?.into(),
+            "--interval" => {
+                let value = value()?;
+                config.interval = value
+                    .parse()
+                    .ok()
+                    .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                    .ok_or_else(|| invalid(value))?;
+            }
+            _ => return Err(format!("Unexpected argument: {}", arg)),
+        }
+    }
+    Ok(FetchArgs { days, config })
+}
+
+fn fetch(args: FetchArgs) -> ExitCode {
+    let days = match args.days {
+        Days::All => ALL_DAYS.collect(),
+        Days::Single(day) if ALL_DAYS.contains(&day) => vec![day],
+        Days::Single(day) => {
+            eprintln!("There is no day {}", day);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut fetcher = Fetcher::new(args.config);
+    let mut success = true;
+    for day in days {
+        match fetcher.fetch(day) {
+            Ok(Fetched::Cached(path)) => {
+                println!("Day {:>2}: already cached in {}", day, path.display())
+            }
+            Ok(Fetched::Downloaded(path)) => {
+                println!("Day {:>2}: downloaded to {}", day, path.display())
+            }
+            Err(err) => {
+                eprintln!("Day {:>2}: {}", day, err);
+                success = false;
+            }
+        }
+    }
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn usage_error(err: String) -> ExitCode {
+    eprintln!("{}\n\n{}", err, USAGE);
+    ExitCode::from(2)
+}
+
+fn main() -> ExitCode {
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("run") => parse_run_args(args).map_or_else(usage_error, run),
+        Some("bench") => parse_bench_args(args).map_or_else(usage_error, bench),
+        Some("verify") => parse_verify_args(args).map_or_else(usage_error, verify),
+        Some("fetch") => parse_fetch_args(args).map_or_else(usage_error, fetch),
+        Some("help" | "--help" | "-h") => {
+            println!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        Some(command) => usage_error(format!("Unknown command: {}", command)),
+        None => usage_error("Missing command".into()),
+    }
+}
@@ -0,0 +1,247 @@
+use crate::{
+    bench::{self, Measurement, Step},
+    loaders::{parse_file, reader_to_string},
+    Result,
+};
+use std::{
+    fmt::{self, Display},
+    io::Read,
+    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// Common interface of every puzzle. Each `aocN` module exposes a unit struct `Puzzle` which
+/// implements this trait on top of the module's own `answer1`/`answer2` functions.
+pub trait Day {
+    /// Day of December the puzzle was released
+    const DAY: u8;
+    /// Path to the puzzle input, relative to the crate root
+    const DATA: &'static str;
+
+    type Input: Clone;
+    type Answer1: Answer;
+    type Answer2: Answer;
+
+    /// Parse the full text of a puzzle input
+    fn parse(input: &str) -> Result<Self::Input>;
+
+    fn part1(input: Self::Input) -> Self::Answer1;
+
+    fn part2(input: Self::Input) -> Self::Answer2;
+
+    /// Read and parse a puzzle input from a file
+    fn load(filename: impl AsRef<Path>) -> Result<Self::Input> {
+        parse_file(filename, Self::parse)
+    }
+
+    /// Read and parse a puzzle input from any reader, e.g. stdin
+    fn read(reader: impl Read) -> Result<Self::Input> {
+        Self::parse(&reader_to_string(reader)?)
+    }
+}
+
+/// An answer to a part of a puzzle, either one which is always found or a `Result` for puzzles
+/// which may have no answer for some inputs
+pub trait Answer {
+    fn into_answer(self) -> Result<String>;
+}
+
+macro_rules! impl_answer {
+    ($($answer:ty),*) => {
+        $(
+            impl Answer for $answer {
+                fn into_answer(self) -> Result<String> {
+                    Ok(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_answer!(
+    i32,
+    i64,
+    u32,
+    u64,
+    usize,
+    String,
+    &'static str,
+    num_bigint::BigUint
+);
+
+impl<T: Answer> Answer for Result<T> {
+    fn into_answer(self) -> Result<String> {
+        self?.into_answer()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const BOTH: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            _ => Err(format!("No such part: {}", s)),
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// Answer to one part of a puzzle together with how long it took to get it
+#[derive(Debug)]
+pub struct Run {
+    pub answer: String,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+}
+
+/// Object safe version of `Day`, allowing days with different input and answer types to be
+/// stored and called uniformly. Implemented for every `Day`.
+pub trait Solution: Sync {
+    fn day(&self) -> u8;
+
+    fn data(&self) -> &'static str;
+
+    /// Parse `input` and solve the given part of the puzzle, formatting the answer
+    fn solve(&self, input: &str, part: Part) -> Result<String>;
+
+    /// Same as `solve`, but also time the parsing and solving steps
+    fn run(&self, input: &str, part: Part) -> Result<Run>;
+
+    /// Repeatedly time parsing `input` and solving each part from the parsed input
+    fn bench(&self, input: &str, config: &bench::Config) -> Result<Vec<Measurement>>;
+}
+
+impl<D: Day + Sync> Solution for D {
+    fn day(&self) -> u8 {
+        D::DAY
+    }
+
+    fn data(&self) -> &'static str {
+        D::DATA
+    }
+
+    fn solve(&self, input: &str, part: Part) -> Result<String> {
+        let input = D::parse(input)?;
+        match part {
+            Part::One => D::part1(input).into_answer(),
+            Part::Two => D::part2(input).into_answer(),
+        }
+    }
+
+    fn run(&self, input: &str, part: Part) -> Result<Run> {
+        let start = Instant::now();
+        let input = D::parse(input)?;
+        let parse_time = start.elapsed();
+
+        let start = Instant::now();
+        let answer = match part {
+            Part::One => D::part1(input).into_answer(),
+            Part::Two => D::part2(input).into_answer(),
+        }?;
+        let solve_time = start.elapsed();
+
+        Ok(Run {
+            answer,
+            parse_time,
+            solve_time,
+        })
+    }
+
+    fn bench(&self, input: &str, config: &bench::Config) -> Result<Vec<Measurement>> {
+        let parsed = D::parse(input)?;
+        Ok(Step::ALL
+            .into_iter()
+            .map(|step| {
+                let time = match step {
+                    Step::Parse => bench::measure(config, || input, D::parse),
+                    Step::Part(Part::One) => bench::measure(config, || parsed.clone(), D::part1),
+                    Step::Part(Part::Two) => bench::measure(config, || parsed.clone(), D::part2),
+                };
+                Measurement {
+                    day: D::DAY,
+                    step,
+                    time,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aoc1, get_day, Error, DAYS};
+
+    #[test]
+    fn test_registry_is_ordered() {
+        assert!(DAYS.windows(2).all(|days| days[0].day() < days[1].day()));
+        assert!(DAYS.iter().all(|day| get_day(day.day()).is_some()));
+    }
+
+    #[test]
+    fn test_solve_mock_data() {
+        let mock_data = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n";
+        let day = get_day(1).unwrap();
+        assert_eq!(day.data(), aoc1::DATA);
+        assert_eq!(day.solve(mock_data, Part::One).unwrap(), "7");
+        assert_eq!(day.solve(mock_data, Part::Two).unwrap(), "5");
+    }
+
+    #[test]
+    fn test_run() {
+        let input = crate::file_to_string(aoc1::DATA).unwrap();
+        let run = get_day(1).unwrap().run(&input, Part::One).unwrap();
+        assert_eq!(run.answer, "1624");
+    }
+
+    #[test]
+    fn test_load_and_read() {
+        let input = aoc1::load(aoc1::DATA).unwrap();
+        assert_eq!(aoc1::Puzzle::load(aoc1::DATA).unwrap(), input);
+        let file = std::fs::File::open(aoc1::DATA).unwrap();
+        assert_eq!(aoc1::Puzzle::read(file).unwrap(), input);
+    }
+
+    #[test]
+    fn test_answer() {
+        assert_eq!(Ok::<u32, Error>(5).into_answer().unwrap(), "5");
+        let unsolvable: Result<u32> = Err(Error::solve("No answer"));
+        assert_eq!(
+            unsolvable.into_answer().unwrap_err().to_string(),
+            "No answer"
+        );
+    }
+
+    #[test]
+    fn test_parse_part() {
+        assert_eq!("1".parse(), Ok(Part::One));
+        assert_eq!("2".parse(), Ok(Part::Two));
+        assert!("3".parse::<Part>().is_err());
+    }
+
+    #[test]
+    fn test_solve_bad_data() {
+        assert!(get_day(1).unwrap().solve("199\nfoo\n", Part::One).is_err());
+    }
+}
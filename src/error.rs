@@ -0,0 +1,155 @@
+use std::{
+    error,
+    fmt::{self, Display},
+    io,
+    path::{Path, PathBuf},
+};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The input could not be opened or read
+    Io {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+    /// Part of the input could not be parsed. `line` and `column` are 1-indexed and point at the
+    /// start of `text`, the offending piece of input.
+    Parse {
+        path: Option<PathBuf>,
+        line: usize,
+        column: usize,
+        text: String,
+        reason: String,
+    },
+    /// A puzzle input could not be downloaded
+    Fetch { url: String, reason: String },
+    /// The input was read fine, but the puzzle has no answer for it
+    Solve { reason: String },
+}
+
+impl Error {
+    pub fn io(path: impl AsRef<Path>, source: io::Error) -> Self {
+        Error::Io {
+            path: Some(path.as_ref().to_owned()),
+            source,
+        }
+    }
+
+    pub fn parse(line: usize, column: usize, text: &str, reason: impl Display) -> Self {
+        Error::Parse {
+            path: None,
+            line,
+            column,
+            text: text.to_owned(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Parse error pointing at `text`, which must be a slice of `input`
+    pub fn at(input: &str, text: &str, reason: impl Display) -> Self {
+        let offset = (text.as_ptr() as usize).wrapping_sub(input.as_ptr() as usize);
+        let before = input.get(..offset).expect("text is not part of input");
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count()
+            + 1;
+        Self::parse(line, column, text, reason)
+    }
+
+    pub fn fetch(url: &str, reason: impl Display) -> Self {
+        Error::Fetch {
+            url: url.to_owned(),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn solve(reason: impl Display) -> Self {
+        Error::Solve {
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Move the position of an error from parsing `section`, which must be a slice of `input`, to
+    /// be relative to the start of `input`
+    pub fn within(mut self, input: &str, section: &str) -> Self {
+        if let Error::Parse { line, column, .. } = &mut self {
+            if let Error::Parse {
+                line: section_line,
+                column: section_column,
+                ..
+            } = Error::at(input, section, "")
+            {
+                if *line == 1 {
+                    *column += section_column - 1;
+                }
+                *line += section_line - 1;
+            }
+        }
+        self
+    }
+
+    /// Attach the path of the file being read, unless the error already has one
+    pub fn with_path(mut self, filename: impl AsRef<Path>) -> Self {
+        if let Error::Io {
+            path: path @ None, ..
+        }
+        | Error::Parse {
+            path: path @ None, ..
+        } = &mut self
+        {
+            *path = Some(filename.as_ref().to_owned());
+        }
+        self
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => {
+                write_path(f, path)?;
+                write!(f, " {}", source)
+            }
+            Error::Parse {
+                path,
+                line,
+                column,
+                text,
+                reason,
+            } => {
+                write_path(f, path)?;
+                write!(f, "{}:{}: {} (found {:?})", line, column, reason, text)
+            }
+            Error::Fetch { url, reason } => write!(f, "{}: {}", url, reason),
+            Error::Solve { reason } => write!(f, "{}", reason),
+        }
+    }
+}
+
+fn write_path(f: &mut fmt::Formatter<'_>, path: &Option<PathBuf>) -> fmt::Result {
+    match path {
+        Some(path) => write!(f, "{}:", path.display()),
+        None => write!(f, "<input>:"),
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(source: io::Error) -> Self {
+        Error::Io { path: None, source }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Parse { .. } | Error::Fetch { .. } | Error::Solve { .. } => None,
+        }
+    }
+}
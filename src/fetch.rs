@@ -0,0 +1,267 @@
+use crate::{Error, Result};
+use std::{
+    env, fs,
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+
+pub const BASE_URL: &str = "https://adventofcode.com/2021";
+/// Directory the inputs are cached in, matching the `DATA` paths of the days
+pub const INPUT_DIR: &str = "input";
+/// Environment variable (or `.env` entry) holding the session cookie
+pub const COOKIE: &str = "COOKIE";
+
+const USER_AGENT: &str = concat!("github.com/skogsvik/AoC2021 by ", env!("CARGO_PKG_AUTHORS"));
+/// Give up instead of waiting when the server asks us to back off for longer than this
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Session cookie from the environment, falling back to a `COOKIE=...` line in `.env`. A bare
+/// token is turned into a `session=<token>` cookie.
+pub fn cookie_from_env() -> Option<String> {
+    let cookie = env::var(COOKIE).ok().or_else(|| {
+        fs::read_to_string(".env").ok()?.lines().find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == COOKIE).then(|| value.trim().trim_matches(['"', '\'']).to_owned())
+        })
+    })?;
+    if cookie.contains('=') {
+        Some(cookie)
+    } else {
+        Some(format!("session={}", cookie))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// URL of the event, inputs are fetched from `<base_url>/day/<day>/input`
+    pub base_url: String,
+    pub cookie: Option<String>,
+    pub dir: PathBuf,
+    /// Minimum time between two requests
+    pub interval: Duration,
+    /// How many times to retry when rate limited (HTTP 429 or 503)
+    pub retries: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            base_url: BASE_URL.into(),
+            cookie: None,
+            dir: INPUT_DIR.into(),
+            interval: Duration::from_secs(5),
+            retries: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fetched {
+    /// The input was already cached, nothing was downloaded
+    Cached(PathBuf),
+    Downloaded(PathBuf),
+}
+
+/// Downloads puzzle inputs into the cache directory, never requesting more often than the
+/// configured interval
+pub struct Fetcher {
+    config: Config,
+    agent: ureq::Agent,
+    not_before: Option<Instant>,
+}
+
+impl Fetcher {
+    pub fn new(config: Config) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .user_agent(USER_AGENT)
+            .timeout(Duration::from_secs(30))
+            .build();
+        Fetcher {
+            config,
+            agent,
+            not_before: None,
+        }
+    }
+
+    pub fn path(&self, day: u8) -> PathBuf {
+        self.config.dir.join(format!("aoc{}", day))
+    }
+
+    /// Download the input of `day` unless it is already cached
+    pub fn fetch(&mut self, day: u8) -> Result<Fetched> {
+        let path = self.path(day);
+        if path.exists() {
+            return Ok(Fetched::Cached(path));
+        }
+        let input = self.download(day)?;
+        fs::create_dir_all(&self.config.dir).map_err(|err| Error::io(&self.config.dir, err))?;
+        // Write to a temporary file first so that an interrupted write is never taken as cached
+        let partial = path.with_extension("part");
+        fs::write(&partial, input).map_err(|err| Error::io(&partial, err))?;
+        fs::rename(&partial, &path).map_err(|err| Error::io(&path, err))?;
+        Ok(Fetched::Downloaded(path))
+    }
+
+    fn download(&mut self, day: u8) -> Result<String> {
+        let url = format!(
+            "{}/day/{}/input",
+            self.config.base_url.trim_end_matches('/'),
+            day
+        );
+        let cookie = self.config.cookie.clone().ok_or_else(|| {
+            Error::fetch(
+                &url,
+                format!(
+                    "No session cookie, set {} in the environment or .env",
+                    COOKIE
+                ),
+            )
+        })?;
+        let mut retries = 0;
+        loop {
+            self.throttle();
+            let response = self.agent.get(&url).set("Cookie", &cookie).call();
+            match response {
+                Ok(response) => {
+                    return response
+                        .into_string()
+                        .map_err(|err| Error::fetch(&url, err))
+                }
+                Err(ureq::Error::Status(429 | 503, response)) if retries < self.config.retries => {
+                    let retry_after = response
+                        .header("Retry-After")
+                        .and_then(|seconds| seconds.trim().parse().ok())
+                        .map_or(Duration::ZERO, Duration::from_secs);
+                    if retry_after > MAX_RETRY_AFTER {
+                        return Err(Error::fetch(
+                            &url,
+                            format!("Rate limited, retry after {:?}", retry_after),
+                        ));
+                    }
+                    self.not_before = Some(Instant::now() + retry_after.max(self.config.interval));
+                    retries += 1;
+                }
+                Err(ureq::Error::Status(code, response)) => {
+                    return Err(Error::fetch(
+                        &url,
+                        format!("HTTP {} {}", code, response.status_text()),
+                    ))
+                }
+                Err(ureq::Error::Transport(transport)) => {
+                    let reason = match std::error::Error::source(&transport) {
+                        Some(source) => format!("{}: {}", transport.kind(), source),
+                        None => transport.kind().to_string(),
+                    };
+                    return Err(Error::fetch(&url, reason));
+                }
+            }
+        }
+    }
+
+    /// Wait until the interval since the previous request has passed
+    fn throttle(&mut self) {
+        if let Some(not_before) = self.not_before {
+            thread::sleep(not_before.saturating_duration_since(Instant::now()));
+        }
+        self.not_before = Some(Instant::now() + self.config.interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        process,
+        thread::JoinHandle,
+    };
+
+    /// Stand-in server answering one connection per canned response, returning the request heads
+    fn serve(responses: &[(&str, &str, &str)]) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/2021", listener.local_addr().unwrap());
+        let responses: Vec<_> = responses
+            .iter()
+            .map(|(status, headers, body)| {
+                format!(
+                    "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    headers,
+                    body.len(),
+                    body
+                )
+            })
+            .collect();
+        let server = thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut request = String::new();
+                    let mut reader = BufReader::new(&stream);
+                    while reader.read_line(&mut request).unwrap() > 2 {}
+                    stream.write_all(response.as_bytes()).unwrap();
+                    request
+                })
+                .collect()
+        });
+        (base_url, server)
+    }
+
+    fn mock_config(base_url: String, name: &str) -> Config {
+        let dir = env::temp_dir().join(format!("aoc2021-fetch-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        Config {
+            base_url,
+            cookie: Some("session=0123abc".into()),
+            dir,
+            interval: Duration::from_millis(50),
+            retries: 2,
+        }
+    }
+
+    #[test]
+    fn test_fetch_and_cache() {
+        let (base_url, server) = serve(&[("200 OK", "", "1\n2\n3\n")]);
+        let mut fetcher = Fetcher::new(mock_config(base_url, "cache"));
+        let path = fetcher.path(3);
+        assert_eq!(fetcher.fetch(3).unwrap(), Fetched::Downloaded(path.clone()));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1\n2\n3\n");
+        assert_eq!(fetcher.fetch(3).unwrap(), Fetched::Cached(path.clone()));
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("GET /2021/day/3/input "));
+        assert!(requests[0].contains("Cookie: session=0123abc\r\n"));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_rate_limited() {
+        let (base_url, server) = serve(&[
+            ("429 Too Many Requests", "Retry-After: 0\r\n", ""),
+            ("200 OK", "", "input"),
+        ]);
+        let mut fetcher = Fetcher::new(mock_config(base_url, "rate-limited"));
+        let start = Instant::now();
+        assert!(matches!(fetcher.fetch(1), Ok(Fetched::Downloaded(_))));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(server.join().unwrap().len(), 2);
+        fs::remove_dir_all(fetcher.config.dir).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_error() {
+        let (base_url, server) = serve(&[("404 Not Found", "", "Not found")]);
+        let mut fetcher = Fetcher::new(mock_config(base_url, "error"));
+        let err = fetcher.fetch(25).unwrap_err();
+        assert!(matches!(&err, Error::Fetch { reason, .. } if reason.contains("404")));
+        assert!(!fetcher.path(25).exists());
+        server.join().unwrap();
+
+        fetcher.config.cookie = None;
+        assert!(matches!(fetcher.fetch(25), Err(Error::Fetch { .. })));
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{Error, Result};
+use std::{
+    convert::Infallible,
+    fmt::{self, Display},
+    iter::StepBy,
+    ops::{Index, IndexMut},
+    slice,
+};
+
+/// Position in a grid as `(row, column)`
+pub type Point = (usize, usize);
+
+/// Offsets to the four orthogonal neighbours
+pub const NEIGHBOURS4: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+/// Offsets to all eight neighbours, including diagonals
+pub const NEIGHBOURS8: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Rectangular grid stored row by row in a flat vector. Cells can be addressed either by `Point`
+/// or by their flat index, which is handy for lookup tables.
+///
+/// By default neighbours outside of the grid are skipped, a wrapping grid instead continues on
+/// the opposite edge.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    wrap: bool,
+}
+
+impl<T> Grid<T> {
+    /// Grid from cells in row order. Panics if `cells` can't be split into rows of `width`.
+    pub fn from_vec(cells: Vec<T>, width: usize) -> Self {
+        assert!(
+            width > 0 && cells.len().is_multiple_of(width),
+            "{} cells can't be split into rows of {}",
+            cells.len(),
+            width
+        );
+        Grid {
+            cells,
+            width,
+            wrap: false,
+        }
+    }
+
+    /// Parse a rectangular map with one cell per character, reporting the position of any
+    /// character rejected by `cell` or any row of the wrong width
+    pub fn parse_with<E: Display>(
+        input: &str,
+        mut cell: impl FnMut(char) -> Result<T, E>,
+    ) -> Result<Self> {
+        let mut cells = Vec::with_capacity(input.len());
+        let mut width = None;
+        for line in input.lines() {
+            let row_start = cells.len();
+            for (i, c) in line.char_indices() {
+                let text = &line[i..i + c.len_utf8()];
+                cells.push(cell(c).map_err(|err| Error::at(input, text, err))?);
+            }
+            let row_width = cells.len() - row_start;
+            let width = *width.get_or_insert(row_width);
+            if row_width != width {
+                let reason = format!("Expected a row of {} cells, found {}", width, row_width);
+                return Err(Error::at(input, line, reason));
+            }
+        }
+        match width {
+            Some(width) if width > 0 => Ok(Self::from_vec(cells, width)),
+            _ => Err(Error::at(
+                input,
+                input,
+                "Expected at least one row of cells",
+            )),
+        }
+    }
+
+    /// Make neighbours and offsets wrap around the edges
+    pub fn wrapping(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.len() / self.width
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, (row, col): Point) -> bool {
+        row < self.height() && col < self.width
+    }
+
+    pub fn index_of(&self, (row, col): Point) -> usize {
+        row * self.width + col
+    }
+
+    pub fn point_of(&self, index: usize) -> Point {
+        (index / self.width, index % self.width)
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.contains(point)
+            .then(|| &self.cells[self.index_of(point)])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        if !self.contains(point) {
+            return None;
+        }
+        let index = self.index_of(point);
+        Some(&mut self.cells[index])
+    }
+
+    /// Move `point` by `(rows, columns)`, `None` if that leaves a non-wrapping grid
+    pub fn offset(&self, (row, col): Point, (rows, columns): (isize, isize)) -> Option<Point> {
+        let shift = |pos: usize, by: isize, size: usize| {
+            if self.wrap {
+                Some((pos as isize + by).rem_euclid(size as isize) as usize)
+            } else {
+                pos.checked_add_signed(by).filter(|&pos| pos < size)
+            }
+        };
+        Some((
+            shift(row, rows, self.height())?,
+            shift(col, columns, self.width)?,
+        ))
+    }
+
+    /// Neighbours of `point` at the given offsets, in the order of the offsets
+    pub fn neighbours<'a>(
+        &'a self,
+        point: Point,
+        offsets: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = Point> + 'a {
+        offsets
+            .iter()
+            .filter_map(move |&offset| self.offset(point, offset))
+    }
+
+    /// Orthogonal neighbours of `point`
+    pub fn neighbours4(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        self.neighbours(point, &NEIGHBOURS4)
+    }
+
+    /// Orthogonal and diagonal neighbours of `point`
+    pub fn neighbours8(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        self.neighbours(point, &NEIGHBOURS8)
+    }
+
+    /// All points in row order
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |index| (index / width, index % width))
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+        self.cells.iter_mut()
+    }
+
+    pub fn indexed_iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(&self.cells)
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> slice::ChunksExact<'_, T> {
+        self.cells.chunks_exact(self.width)
+    }
+
+    pub fn column(&self, column: usize) -> StepBy<slice::Iter<'_, T>> {
+        assert!(column < self.width, "column {} out of bounds", column);
+        self.cells[column..].iter().step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = StepBy<slice::Iter<'_, T>>> {
+        (0..self.width).map(move |column| self.column(column))
+    }
+
+    /// Cells in row order
+    pub fn as_slice(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.cells
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            wrap: self.wrap,
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self::from_vec(vec![value; width * height], width)
+    }
+}
+
+impl Grid<u32> {
+    /// Parse a map of single digits such as `"123\n456\n"`
+    pub fn parse_digits(input: &str) -> Result<Self> {
+        Self::parse_with(input, |c| c.to_digit(10).ok_or("Expected a digit"))
+    }
+}
+
+impl Grid<char> {
+    pub fn parse_chars(input: &str) -> Result<Self> {
+        Self::parse_with(input, Ok::<_, Infallible>)
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        self.get(point)
+            .unwrap_or_else(|| panic!("{:?} is outside of the grid", point))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        self.get_mut(point)
+            .unwrap_or_else(|| panic!("{:?} is outside of the grid", point))
+    }
+}
+
+/// One line per row with the cells written back to back, e.g. the same text a digit or char map
+/// was parsed from
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_digits() {
+        let grid = Grid::parse_digits("123\n456\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.as_slice(), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(grid.to_string(), "123\n456");
+
+        let err = Grid::parse_digits("123\n4a6\n").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Parse {
+                line: 2,
+                column: 2,
+                ..
+            }
+        ));
+        let err = Grid::parse_digits("123\n45\n").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Parse {
+                line: 2,
+                column: 1,
+                ..
+            }
+        ));
+        assert!(Grid::parse_digits("").is_err());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::parse_chars("abc\ndef\nghi").unwrap();
+        let corner = grid
+            .neighbours8((0, 0))
+            .map(|p| grid[p])
+            .collect::<String>();
+        assert_eq!(corner, "bde");
+        let centre = grid
+            .neighbours4((1, 1))
+            .map(|p| grid[p])
+            .collect::<String>();
+        assert_eq!(centre, "bhdf");
+
+        let grid = grid.wrapping(true);
+        let corner = grid
+            .neighbours4((0, 0))
+            .map(|p| grid[p])
+            .collect::<String>();
+        assert_eq!(corner, "gdcb");
+        assert_eq!(grid.offset((2, 2), (1, 1)), Some((0, 0)));
+    }
+
+    #[test]
+    fn test_views() {
+        let grid = Grid::from_vec((0..6).collect(), 3);
+        assert_eq!(grid.row(1), [3, 4, 5]);
+        assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), [2, 5]);
+        assert_eq!(grid.columns().count(), 3);
+        assert_eq!(grid.get((2, 0)), None);
+        let mut grid = grid;
+        assert_eq!(grid.get_mut((usize::MAX, 1)), None);
+        assert_eq!(grid.point_of(grid.index_of((1, 2))), (1, 2));
+        assert_eq!(grid.map(|&x| x * 2)[(1, 0)], 6);
+    }
+}
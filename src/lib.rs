@@ -1,6 +1,15 @@
+pub mod bench;
+mod day;
+mod error;
+pub mod fetch;
+pub mod grid;
 mod loaders;
-#[cfg(test)]
-mod mockers;
+pub mod search;
+pub mod verify;
+
+pub use day::{Day, Part, Run, Solution};
+pub use error::{Error, Result};
+pub use loaders::{file_to_string, reader_to_string};
 
 pub mod aoc1;
 pub mod aoc2;
@@ -20,3 +29,44 @@ pub mod aoc15;
 pub mod aoc16;
 pub mod aoc17;
 pub mod aoc18;
+pub mod aoc19;
+pub mod aoc20;
+pub mod aoc21;
+pub mod aoc22;
+pub mod aoc23;
+pub mod aoc24;
+pub mod aoc25;
+
+/// All implemented days, in order
+pub const DAYS: &[&dyn Solution] = &[
+    &aoc1::Puzzle,
+    &aoc2::Puzzle,
+    &aoc3::Puzzle,
+    &aoc4::Puzzle,
+    &aoc5::Puzzle,
+    &aoc6::Puzzle,
+    &aoc7::Puzzle,
+    &aoc8::Puzzle,
+    &aoc9::Puzzle,
+    &aoc10::Puzzle,
+    &aoc11::Puzzle,
+    &aoc12::Puzzle,
+    &aoc13::Puzzle,
+    &aoc14::Puzzle,
+    &aoc15::Puzzle,
+    &aoc16::Puzzle,
+    &aoc17::Puzzle,
+    &aoc18::Puzzle,
+    &aoc19::Puzzle,
+    &aoc20::Puzzle,
+    &aoc21::Puzzle,
+    &aoc22::Puzzle,
+    &aoc23::Puzzle,
+    &aoc24::Puzzle,
+    &aoc25::Puzzle,
+];
+
+/// Look up an implemented day by its day of December
+pub fn get_day(day: u8) -> Option<&'static dyn Solution> {
+    DAYS.iter().copied().find(|solution| solution.day() == day)
+}
@@ -1,86 +1,50 @@
-use ndarray::Array2;
-use std::{
-    fmt::Debug,
-    fs::{read_to_string, File},
-    io::{prelude::*, BufReader},
-    path::Path,
-    str::FromStr,
-};
+use crate::{Error, Result};
+use std::{fmt::Display, fs::read_to_string, io::Read, path::Path, str::FromStr};
 
-fn buf_open(filename: impl AsRef<Path>) -> BufReader<File> {
-    let file = File::open(filename).expect("No such file");
-    BufReader::new(file)
+pub fn file_to_string(filename: impl AsRef<Path>) -> Result<String> {
+    read_to_string(&filename).map_err(|err| Error::io(filename, err))
 }
 
-pub fn file_to_string(filename: impl AsRef<Path>) -> String {
+/// Read everything from e.g. stdin into a string
+pub fn reader_to_string(mut reader: impl Read) -> Result<String> {
     let mut content = String::new();
-    File::open(filename)
-        .expect("No such file")
-        .read_to_string(&mut content)
-        .expect("Unablet to read UTF-8 data");
-    content
+    reader.read_to_string(&mut content)?;
+    Ok(content)
 }
 
-pub fn file_to<T>(filename: impl AsRef<Path>) -> impl Iterator<Item = T>
+/// Read a file and hand its content to a `str` parser, tagging any error with the path
+pub fn parse_file<T>(
+    filename: impl AsRef<Path>,
+    parser: impl FnOnce(&str) -> Result<T>,
+) -> Result<T> {
+    parser(&file_to_string(&filename)?).map_err(|err| err.with_path(filename))
+}
+
+pub fn file_to<T>(filename: impl AsRef<Path>) -> Result<impl Iterator<Item = T>>
 where
-    T::Err: Debug,
+    T::Err: Display,
     T: FromStr,
 {
-    file_to_lines(filename).map(|l| {
-        l.parse::<T>() // should this be TryFrom?
-            .expect("Failed to parse")
-    })
+    parse_file(filename, str_to)
 }
 
-pub fn file_to_vec<T>(filename: impl AsRef<Path>) -> Vec<T>
+pub fn file_to_vec<T>(filename: impl AsRef<Path>) -> Result<Vec<T>>
 where
-    T::Err: Debug,
+    T::Err: Display,
     T: FromStr,
 {
-    file_to(filename).collect()
+    parse_file(filename, str_to_vec)
 }
 
-pub fn file_to_squashed_2d_vec(filename: impl AsRef<Path>) -> (Vec<u32>, usize) {
-    let data = read_to_string(filename).expect("Failed to open file");
-    let width = data
-        .chars()
-        .position(|c| c == '\n')
-        .unwrap_or(data.len() - 1);
-    let array = data.chars().filter_map(|c| c.to_digit(10)).collect();
-    (array, width)
-}
-
-pub fn file_to_array2(filename: impl AsRef<Path>) -> Array2<u32> {
-    let (data, width) = file_to_squashed_2d_vec(filename);
-    Array2::from_shape_vec((data.len() / width, width), data).expect("Could not build array")
-}
-
-pub fn delimited_file_to<T>(filename: impl AsRef<Path>, delim_byte: u8) -> impl Iterator<Item = T>
+pub fn delimited_file_to<T>(
+    filename: impl AsRef<Path>,
+    delim: char,
+) -> Result<impl Iterator<Item = T>>
 where
     T: FromStr,
-    T::Err: Debug,
+    T::Err: Display,
 {
-    buf_open(filename).split(delim_byte).map(|bytes| {
-        std::str::from_utf8(&bytes.expect("Unexpected IO interruption"))
-            .expect("Failed to read str from utf8")
-            .trim()
-            .parse::<T>()
-            .expect("Failed to parse")
-    })
-}
-
-// pub fn file_chars_as_digits(filename: impl AsRef<Path>) -> impl Iterator<Item = u32> {
-//     buf_open(filename).bytes().map(|byte| {
-//         (byte.expect("Unexpected IO interruption") as char)
-//             .to_digit(10)
-//             .expect("Failed to parse")
-//     })
-// }
-
-pub fn file_to_lines(filename: impl AsRef<Path>) -> impl Iterator<Item = String> {
-    buf_open(filename)
-        .lines()
-        .map(|line| line.expect("Couldn't read line"))
+    parse_file(filename, |input| delimited_str_to(input, delim))
 }
 
 // pub fn file_to_paragraphs(filename: impl AsRef<Path>) -> impl Iterator<Item = Vec<String>> {
@@ -92,3 +56,72 @@ pub fn file_to_lines(filename: impl AsRef<Path>) -> impl Iterator<Item = String>
 //         Some(lines.take_while(|line| !line.is_empty()).collect())
 //     })
 // }
+
+/// Parse `text`, which must be a slice of `input`, reporting its position in `input` on failure
+pub fn parse_at<T>(input: &str, text: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    text.parse().map_err(|err| Error::at(input, text, err))
+}
+
+pub fn str_to<T>(input: &str) -> Result<impl Iterator<Item = T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    Ok(str_to_vec(input)?.into_iter())
+}
+
+pub fn str_to_vec<T>(input: &str) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    input.lines().map(|line| parse_at(input, line)).collect()
+}
+
+pub fn delimited_str_to<T>(input: &str, delim: char) -> Result<impl Iterator<Item = T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    Ok(input
+        .trim()
+        .split(delim)
+        .map(|s| parse_at(input, s.trim()))
+        .collect::<Result<Vec<T>>>()?
+        .into_iter())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_str_to_vec_error_position() {
+        let err = str_to_vec::<u32>("1\n2\nthree\n4").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Parse { line: 3, column: 1, ref text, .. } if text == "three"
+        ));
+    }
+
+    #[test]
+    fn test_delimited_str_to_error_position() {
+        let err = delimited_str_to::<u32>("3,4, x5,1\n", ',').err().unwrap();
+        assert!(matches!(
+            err,
+            Error::Parse { line: 1, column: 6, ref text, .. } if text == "x5"
+        ));
+    }
+
+    #[test]
+    fn test_file_errors_have_path() {
+        let err = file_to_string("input/no_such_day").unwrap_err();
+        assert_eq!(err.to_string().split(':').next(), Some("input/no_such_day"));
+        let err = file_to_vec::<u8>(crate::aoc16::DATA).unwrap_err();
+        assert!(err.to_string().starts_with("input/aoc16:1:1: "));
+    }
+}
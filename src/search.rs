@@ -0,0 +1,220 @@
+use crate::grid::{Grid, Point};
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+/// Anything that can be used as the cost of an edge, `Default` being a cost of zero
+pub trait Cost: Copy + Ord + Add<Output = Self> + Default {}
+
+impl<T: Copy + Ord + Add<Output = T> + Default> Cost for T {}
+
+/// Directed graph with weighted edges, nodes being cheap handles such as grid points
+pub trait Graph {
+    type Node: Copy + Eq + Hash;
+    type Cost: Cost;
+
+    /// Nodes reachable from `node` in one step, along with the cost of each step
+    fn neighbours(&self, node: Self::Node) -> impl Iterator<Item = (Self::Node, Self::Cost)> + '_;
+}
+
+/// Moving to an orthogonally neighbouring cell costs the value of that cell
+impl<C: Cost> Graph for Grid<C> {
+    type Node = Point;
+    type Cost = C;
+
+    fn neighbours(&self, node: Point) -> impl Iterator<Item = (Point, C)> + '_ {
+        self.neighbours4(node).map(|nbor| (nbor, self[nbor]))
+    }
+}
+
+/// Route found by a search, including both the start and the goal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N, C> {
+    pub nodes: Vec<N>,
+    pub cost: C,
+}
+
+/// Manhattan distance, an admissible heuristic for 4-connected grids where every step costs at
+/// least one
+pub fn manhattan((row_a, col_a): Point, (row_b, col_b): Point) -> usize {
+    row_a.abs_diff(row_b) + col_a.abs_diff(col_b)
+}
+
+/// Path with the fewest steps from `start` to the first node satisfying `is_goal`, ignoring
+/// edge costs. The cost of the path is its number of steps.
+pub fn bfs<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    mut is_goal: impl FnMut(G::Node) -> bool,
+) -> Option<Path<G::Node, usize>> {
+    let mut parents = HashMap::from([(start, None)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(current) = queue.pop_front() {
+        if is_goal(current) {
+            let nodes = walk_back(current, |node| parents[&node]);
+            let cost = nodes.len() - 1;
+            return Some(Path { nodes, cost });
+        }
+        for (nbor, _) in graph.neighbours(current) {
+            if let Entry::Vacant(entry) = parents.entry(nbor) {
+                entry.insert(Some(current));
+                queue.push_back(nbor);
+            }
+        }
+    }
+    None
+}
+
+/// Cheapest path from `start` to the first node satisfying `is_goal`
+pub fn dijkstra<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    is_goal: impl FnMut(G::Node) -> bool,
+) -> Option<Path<G::Node, G::Cost>> {
+    a_star(graph, start, is_goal, |_| G::Cost::default())
+}
+
+/// Cheapest path from `start` to the first node satisfying `is_goal`, guided by `heuristic`.
+/// The path is only guaranteed to be the cheapest if the heuristic never overestimates the
+/// remaining cost to a goal. Closed nodes are reopened when a cheaper path to them is found, so
+/// the heuristic doesn't also need to be consistent.
+pub fn a_star<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    mut is_goal: impl FnMut(G::Node) -> bool,
+    mut heuristic: impl FnMut(G::Node) -> G::Cost,
+) -> Option<Path<G::Node, G::Cost>> {
+    // Nodes are numbered in the order they are discovered, so that the heap doesn't require
+    // them to be ordered and the bookkeeping can live in plain vectors
+    let mut ids = HashMap::from([(start, 0)]);
+    let mut nodes = vec![start];
+    let mut g_score = vec![G::Cost::default()];
+    let mut parents = vec![None];
+    let mut closed = vec![false];
+
+    let mut open = BinaryHeap::from([(Reverse(heuristic(start)), 0)]);
+    while let Some((_, current)) = open.pop() {
+        if closed[current] {
+            continue;
+        }
+        if is_goal(nodes[current]) {
+            let path = walk_back(current, |id| parents[id]);
+            return Some(Path {
+                nodes: path.into_iter().map(|id| nodes[id]).collect(),
+                cost: g_score[current],
+            });
+        }
+        closed[current] = true;
+
+        for (nbor, cost) in graph.neighbours(nodes[current]) {
+            let candidate_g_score = g_score[current] + cost;
+            let nbor = match ids.entry(nbor) {
+                Entry::Occupied(entry) => {
+                    let id = *entry.get();
+                    if candidate_g_score >= g_score[id] {
+                        continue;
+                    }
+                    g_score[id] = candidate_g_score;
+                    parents[id] = Some(current);
+                    closed[id] = false;
+                    id
+                }
+                Entry::Vacant(entry) => {
+                    let id = *entry.insert(nodes.len());
+                    nodes.push(nbor);
+                    g_score.push(candidate_g_score);
+                    parents.push(Some(current));
+                    closed.push(false);
+                    id
+                }
+            };
+            let f_score = candidate_g_score + heuristic(nodes[nbor]);
+            open.push((Reverse(f_score), nbor));
+        }
+    }
+    None
+}
+
+/// Follow the parents from `end` back to the start, returning the path from start to end
+fn walk_back<N: Copy>(end: N, mut parent: impl FnMut(N) -> Option<N>) -> Vec<N> {
+    let mut path = vec![end];
+    while let Some(node) = parent(*path.last().unwrap()) {
+        path.push(node);
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Adjacency list where the direct edge 0 -> 3 is more expensive than going around
+    struct MockGraph(Vec<Vec<(usize, u32)>>);
+
+    impl Graph for MockGraph {
+        type Node = usize;
+        type Cost = u32;
+
+        fn neighbours(&self, node: usize) -> impl Iterator<Item = (usize, u32)> + '_ {
+            self.0[node].iter().copied()
+        }
+    }
+
+    fn mock_graph() -> MockGraph {
+        MockGraph(vec![
+            vec![(1, 1), (3, 10)],
+            vec![(2, 1)],
+            vec![(3, 1)],
+            vec![],
+            vec![(0, 1)],
+        ])
+    }
+
+    #[test]
+    fn test_bfs() {
+        let path = bfs(&mock_graph(), 0, |node| node == 3).unwrap();
+        assert_eq!(path.nodes, [0, 3]);
+        assert_eq!(path.cost, 1);
+        assert_eq!(bfs(&mock_graph(), 0, |node| node == 4), None);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let path = dijkstra(&mock_graph(), 0, |node| node == 3).unwrap();
+        assert_eq!(path.nodes, [0, 1, 2, 3]);
+        assert_eq!(path.cost, 3);
+        assert_eq!(dijkstra(&mock_graph(), 3, |node| node == 0), None);
+    }
+
+    #[test]
+    fn test_a_star_inconsistent_heuristic() {
+        // Going through 1 is cheaper, but its estimate is high enough that 2 is closed first
+        let graph = MockGraph(vec![
+            vec![(1, 1), (2, 3)],
+            vec![(2, 1)],
+            vec![(3, 5)],
+            vec![],
+        ]);
+        let heuristic = |node| [0, 5, 0, 0][node];
+        let path = a_star(&graph, 0, |node| node == 3, heuristic).unwrap();
+        assert_eq!(path.nodes, [0, 1, 2, 3]);
+        assert_eq!(path.cost, 7);
+    }
+
+    #[test]
+    fn test_a_star_on_grid() {
+        let grid = Grid::parse_digits("1163\n1381\n2136\n").unwrap();
+        let goal = (2, 3);
+        let path = a_star(&grid, (0, 0), |p| p == goal, |p| manhattan(p, goal) as u32).unwrap();
+        assert_eq!(path.cost, 13);
+        assert_eq!(path.nodes.first(), Some(&(0, 0)));
+        assert_eq!(path.nodes.last(), Some(&goal));
+        let cost: u32 = path.nodes[1..].iter().map(|&p| grid[p]).sum();
+        assert_eq!(cost, path.cost);
+        assert_eq!(dijkstra(&grid, (0, 0), |p| p == goal).unwrap().cost, 13);
+    }
+}
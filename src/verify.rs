@@ -0,0 +1,198 @@
+use crate::{file_to_string, get_day, loaders::parse_file, Error, Part, Result};
+use serde::Deserialize;
+use std::{
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+};
+
+/// Default registry of expected answers, relative to the crate root
+pub const ANSWERS: &str = "answers.toml";
+
+/// Expected answers to a single puzzle input
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Entry {
+    pub day: u8,
+    /// Path to the input, relative to the crate root
+    pub path: PathBuf,
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+impl Entry {
+    pub fn expected(&self, part: Part) -> Option<&str> {
+        match part {
+            Part::One => self.part1.as_deref(),
+            Part::Two => self.part2.as_deref(),
+        }
+    }
+}
+
+/// Registry of expected answers, stored as TOML with one `[[input]]` table per `Entry`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Answers {
+    #[serde(default, rename = "input")]
+    pub inputs: Vec<Entry>,
+}
+
+impl Answers {
+    pub fn parse(input: &str) -> Result<Self> {
+        toml::from_str(input).map_err(|err| {
+            let span = err.span().unwrap_or(input.len()..input.len());
+            let text = input.get(span).unwrap_or_default();
+            Error::at(input, text, err.message())
+        })
+    }
+
+    pub fn load(filename: impl AsRef<Path>) -> Result<Self> {
+        parse_file(filename, Self::parse)
+    }
+
+    /// Solve every registered input of `day` (or of every day if `None`) and compare the answers
+    /// against the expected ones. Parts without an expected answer are skipped.
+    pub fn verify(&self, day: Option<u8>) -> Vec<Check> {
+        self.inputs
+            .iter()
+            .filter(|entry| day.is_none_or(|day| entry.day == day))
+            .flat_map(|entry| {
+                let parts = Part::BOTH
+                    .into_iter()
+                    .filter(|&part| entry.expected(part).is_some());
+                let input = file_to_string(&entry.path);
+                parts
+                    .map(|part| Check {
+                        day: entry.day,
+                        part,
+                        path: entry.path.clone(),
+                        outcome: check(entry, part, &input),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+fn check(entry: &Entry, part: Part, input: &Result<String>) -> Outcome {
+    let Some(day) = get_day(entry.day) else {
+        return Outcome::NotImplemented;
+    };
+    let expected = entry.expected(part).unwrap_or_default();
+    let found = input
+        .as_ref()
+        .map_err(|err| err.to_string())
+        .and_then(|input| {
+            day.solve(input, part)
+                .map_err(|err| err.with_path(&entry.path).to_string())
+        });
+    match found {
+        Ok(found) if found == expected => Outcome::Correct,
+        Ok(found) => Outcome::Mismatch {
+            expected: expected.to_owned(),
+            found,
+        },
+        Err(err) => Outcome::Failed(err),
+    }
+}
+
+/// Result of checking one part of one registered input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub day: u8,
+    pub part: Part,
+    pub path: PathBuf,
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Correct,
+    Mismatch {
+        expected: String,
+        found: String,
+    },
+    /// The input could not be read or parsed
+    Failed(String),
+    NotImplemented,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Correct => write!(f, "ok"),
+            Outcome::Mismatch { expected, found } => {
+                write!(f, "expected {:?}, found {:?}", expected, found)
+            }
+            Outcome::Failed(err) => write!(f, "failed: {}", err),
+            Outcome::NotImplemented => write!(f, "day is not implemented"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DAYS;
+
+    const MOCK_ANSWERS: &str = r#"
+[[input]]
+day = 1
+path = "input/aoc1"
+part1 = "1624"
+part2 = "1"
+
+[[input]]
+day = 1
+path = "input/missing"
+part1 = "1"
+
+[[input]]
+day = 31
+path = "input/aoc1"
+part2 = "1"
+"#;
+
+    #[test]
+    fn test_verify_mock_answers() {
+        let answers = Answers::parse(MOCK_ANSWERS).unwrap();
+        let outcomes: Vec<_> = answers
+            .verify(None)
+            .into_iter()
+            .map(|check| (check.day, check.part, check.outcome))
+            .collect();
+        assert_eq!(outcomes.len(), 4);
+        assert_eq!(outcomes[0], (1, Part::One, Outcome::Correct));
+        assert_eq!(
+            outcomes[1],
+            (
+                1,
+                Part::Two,
+                Outcome::Mismatch {
+                    expected: "1".into(),
+                    found: "1653".into()
+                }
+            )
+        );
+        assert!(matches!(outcomes[2], (1, Part::One, Outcome::Failed(_))));
+        assert_eq!(outcomes[3], (31, Part::Two, Outcome::NotImplemented));
+        assert_eq!(answers.verify(Some(31)).len(), 1);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = Answers::parse("[[input]]\nday = 1\npath = 2\n").unwrap_err();
+        assert!(matches!(err, Error::Parse { line: 3, .. }), "{}", err);
+    }
+
+    #[test]
+    fn test_registered_answers() {
+        let answers = Answers::load(ANSWERS).unwrap();
+        // Days without a fetched input have nothing to check against
+        for day in DAYS.iter().filter(|day| Path::new(day.data()).exists()) {
+            assert!(answers.inputs.iter().any(|entry| entry.day == day.day()));
+        }
+        for check in answers.verify(None) {
+            assert_eq!(check.outcome, Outcome::Correct, "{:?}", check);
+        }
+    }
+}